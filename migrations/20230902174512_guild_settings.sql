@@ -0,0 +1,6 @@
+ALTER TABLE watchers ADD COLUMN guild_id BIGINT;
+
+CREATE TABLE guild_settings (
+    guild_id BIGINT PRIMARY KEY,
+    channel_id BIGINT
+);
@@ -0,0 +1,7 @@
+ALTER TABLE watchers ADD COLUMN suspended BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE user_settings (
+    user_id BIGINT PRIMARY KEY,
+    delivery TEXT NOT NULL DEFAULT 'channel',
+    dm_failures INTEGER NOT NULL DEFAULT 0
+);
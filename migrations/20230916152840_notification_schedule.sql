@@ -0,0 +1,19 @@
+ALTER TABLE user_settings
+    ADD COLUMN timezone TEXT,
+    ADD COLUMN quiet_start TIME,
+    ADD COLUMN quiet_end TIME,
+    ADD COLUMN digest_time TIME;
+
+CREATE TABLE notification_queue (
+    user_id BIGINT NOT NULL,
+    guild_id BIGINT,
+    course TEXT NOT NULL,
+    semester TEXT NOT NULL,
+    career TEXT NOT NULL,
+    section TEXT NOT NULL,
+    old_timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
+    old JSONB NOT NULL,
+    new_timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
+    new JSONB NOT NULL,
+    deliver_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
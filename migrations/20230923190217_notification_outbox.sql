@@ -0,0 +1,47 @@
+CREATE TABLE notifications (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    guild_id BIGINT,
+    course TEXT NOT NULL,
+    semester TEXT NOT NULL,
+    career TEXT NOT NULL,
+    section TEXT NOT NULL,
+    old_timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
+    old JSONB NOT NULL,
+    new_timestamp TIMESTAMP WITH TIME ZONE NOT NULL,
+    new JSONB NOT NULL,
+    deliver_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    delivered_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX notifications_pending ON notifications (deliver_at) WHERE delivered_at IS NULL;
+
+INSERT INTO notifications (
+    user_id,
+    guild_id,
+    course,
+    semester,
+    career,
+    section,
+    old_timestamp,
+    old,
+    new_timestamp,
+    new,
+    deliver_at
+)
+SELECT
+    user_id,
+    guild_id,
+    course,
+    semester,
+    career,
+    section,
+    old_timestamp,
+    old,
+    new_timestamp,
+    new,
+    deliver_at
+FROM notification_queue;
+
+DROP TABLE notification_queue;
@@ -0,0 +1,2 @@
+-- the locale of the user's last interaction, used to localize their notifications
+ALTER TABLE user_settings ADD COLUMN locale TEXT;
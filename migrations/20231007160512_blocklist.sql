@@ -0,0 +1,5 @@
+CREATE TABLE blocklist (
+    user_id BIGINT PRIMARY KEY,
+    reason TEXT,
+    blocked_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,10 @@
+-- when each class last changed, classes that stay the same for long are polled less often
+CREATE TABLE class_activity (
+    course TEXT NOT NULL,
+    semester TEXT NOT NULL,
+    career TEXT NOT NULL,
+    section TEXT NOT NULL,
+    changed_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    is_open BOOLEAN,
+    PRIMARY KEY (course, semester, career, section)
+);
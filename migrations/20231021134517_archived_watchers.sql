@@ -0,0 +1,16 @@
+-- watches of semesters past add/drop, kept until their watchers are told that they expired
+CREATE TABLE archived_watchers (
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    guild_id BIGINT,
+    course TEXT NOT NULL,
+    semester TEXT NOT NULL,
+    career TEXT NOT NULL,
+    section TEXT NOT NULL,
+    archived_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    deliver_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    delivered_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX archived_watchers_pending ON archived_watchers (deliver_at) WHERE delivered_at IS NULL;
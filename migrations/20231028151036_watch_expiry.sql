@@ -0,0 +1,4 @@
+ALTER TABLE watchers ADD COLUMN expires_at TIMESTAMP WITH TIME ZONE;
+-- removed once a notification about the class is sent
+ALTER TABLE watchers ADD COLUMN once BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE watchers ADD COLUMN snoozed_until TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,2 @@
+-- semester name, e.g. Fall2023, used when a command is given none
+ALTER TABLE user_settings ADD COLUMN semester TEXT;
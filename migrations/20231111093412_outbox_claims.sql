@@ -0,0 +1,14 @@
+-- rows are claimed while being delivered, so that nothing is sent twice, and given up on once
+-- they fail too many times
+ALTER TABLE notifications
+    ADD COLUMN claimed_until TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN dead_at TIMESTAMP WITH TIME ZONE;
+
+ALTER TABLE archived_watchers
+    ADD COLUMN claimed_until TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN dead_at TIMESTAMP WITH TIME ZONE;
+
+UPDATE notifications SET dead_at = NOW() WHERE delivered_at IS NULL AND attempts >= 10;
+UPDATE archived_watchers SET dead_at = NOW() WHERE delivered_at IS NULL AND attempts >= 10;
+
+CREATE INDEX notifications_delivered ON notifications (delivered_at) WHERE delivered_at IS NOT NULL;
@@ -0,0 +1,5 @@
+-- classes are polled once `next_poll_at` is due, which is `NULL` until they're first polled, and
+-- classes that have never been fetched are scheduled too, without having changed
+ALTER TABLE class_activity
+    ADD COLUMN next_poll_at TIMESTAMP WITH TIME ZONE,
+    ALTER COLUMN changed_at DROP NOT NULL;
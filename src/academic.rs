@@ -0,0 +1,159 @@
+use std::{collections::HashMap, fs, path::Path, str::FromStr};
+
+use serde::Deserialize;
+use sqlx::types::chrono::{Duration, NaiveDate, Utc};
+use toml::value::Datetime;
+use ubs_lib::Semester;
+
+use crate::catalog;
+
+// add/drop usually ends a week into classes
+const ADD_DROP_DAYS: i64 = 7;
+
+// both days are included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Dates {
+    pub registration: Option<Window>,
+    pub add_drop: Option<Window>,
+}
+
+// when seats actually move for each semester, read from a file such as:
+//
+// [Fall2023]
+// registration = [2023-04-10, 2023-08-27]
+// add-drop = [2023-08-28, 2023-09-05]
+#[derive(Debug, Default)]
+pub struct AcademicCalendar {
+    // keyed by semester name, e.g. "Fall2023"
+    semesters: HashMap<String, Dates>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct RawDates {
+    registration: Option<[Datetime; 2]>,
+    add_drop: Option<[Datetime; 2]>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CalendarError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Toml(#[from] toml::de::Error),
+    #[error("`{0}` isn't a semester name, e.g. Fall2023")]
+    Semester(String),
+    #[error("dates for `{0}` must be a start and end date, e.g. [2023-08-28, 2023-09-05]")]
+    Window(String),
+}
+
+impl Window {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start <= date && date <= self.end
+    }
+}
+
+impl AcademicCalendar {
+    pub fn load(path: &Path) -> Result<AcademicCalendar, CalendarError> {
+        let raw: HashMap<String, RawDates> = toml::from_str(&fs::read_to_string(path)?)?;
+        let mut semesters = HashMap::new();
+        for (name, dates) in raw {
+            if catalog::parse_semester(&name).is_none() {
+                return Err(CalendarError::Semester(name));
+            }
+
+            let registration = window(&name, dates.registration)?;
+            let add_drop = window(&name, dates.add_drop)?;
+            semesters.insert(
+                name,
+                Dates {
+                    registration,
+                    add_drop,
+                },
+            );
+        }
+
+        Ok(AcademicCalendar { semesters })
+    }
+
+    // anything that isn't configured is approximated, add/drop from the first day of classes and
+    // registration not at all, since it varies too much between semesters
+    pub fn dates(&self, semester_id: &str) -> Dates {
+        let configured = catalog::semester_name(semester_id)
+            .and_then(|x| self.semesters.get(&x).copied())
+            .unwrap_or_default();
+        Dates {
+            registration: configured.registration,
+            add_drop: configured.add_drop.or_else(|| {
+                let (start, _) = catalog::semester_dates(semester_id)?;
+                Some(Window {
+                    start,
+                    end: start + Duration::days(ADD_DROP_DAYS),
+                })
+            }),
+        }
+    }
+
+    // whether students are registering for, adding or dropping classes of the semester
+    pub fn is_busy(&self, semester_id: &str, date: NaiveDate) -> bool {
+        let dates = self.dates(semester_id);
+        [dates.registration, dates.add_drop]
+            .iter()
+            .flatten()
+            .any(|x| x.contains(date))
+    }
+
+    // names of every known semester that hasn't passed add/drop, soonest first
+    pub fn semesters(&self, date: NaiveDate) -> Vec<String> {
+        catalog::known_semesters()
+            .filter(|x| Semester::from_str(x).map_or(false, |x| !self.is_over(x.id(), date)))
+            .collect()
+    }
+
+    // the spring or fall semester that students are most likely choosing classes for, summer and
+    // winter sessions are only used when asked for
+    pub fn default_semester(&self, date: NaiveDate) -> Option<String> {
+        self.semesters(date)
+            .into_iter()
+            .find(|x| catalog::is_main_term(x))
+    }
+
+    // once add/drop ends seats no longer move, so the semester's watches expire
+    pub fn is_over(&self, semester_id: &str, date: NaiveDate) -> bool {
+        self.dates(semester_id)
+            .add_drop
+            .map_or(false, |x| date > x.end)
+    }
+}
+
+// dates are local to UB
+pub fn today() -> NaiveDate {
+    Utc::now().with_timezone(&catalog::TIMEZONE).date_naive()
+}
+
+fn window(name: &str, dates: Option<[Datetime; 2]>) -> Result<Option<Window>, CalendarError> {
+    let [start, end] = match dates {
+        Some(dates) => dates,
+        None => return Ok(None),
+    };
+    match (date(&start), date(&end)) {
+        (Some(start), Some(end)) if start <= end => Ok(Some(Window { start, end })),
+        _ => Err(CalendarError::Window(name.to_owned())),
+    }
+}
+
+// only plain dates are accepted, times wouldn't mean anything here
+fn date(value: &Datetime) -> Option<NaiveDate> {
+    match (value.date, value.time) {
+        (Some(date), None) => {
+            NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into())
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,185 @@
+use poise::serenity_prelude::{GuildId, UserId};
+use sqlx::{
+    types::chrono::{DateTime, Utc},
+    PgConnection, PgPool,
+};
+
+use crate::{
+    cache::Query,
+    notifier::Recipient,
+    outbox::{CLAIM_DURATION, MAX_ATTEMPTS},
+};
+
+// a watch that expired and whose watcher hasn't been told yet
+#[derive(Debug)]
+pub struct Expired {
+    pub id: i64,
+    pub recipient: Recipient,
+    pub query: Query,
+}
+
+// watches are moved here once their semester's add/drop period ends, since seats no longer move
+#[derive(Debug)]
+pub struct Archive {
+    database: PgPool,
+}
+
+impl Archive {
+    pub fn new(database: PgPool) -> Archive {
+        Archive { database }
+    }
+
+    // returns every user that should be told, suspended watches are archived without a notice
+    pub async fn archive(
+        connection: &mut PgConnection,
+        semesters: &[String],
+        now: DateTime<Utc>,
+    ) -> Result<Vec<UserId>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+WITH expired AS (
+  DELETE FROM watchers
+  WHERE semester = ANY($1)
+  RETURNING user_id, guild_id, course, semester, career, section, suspended
+), archived AS (
+  INSERT INTO archived_watchers (
+    user_id,
+    guild_id,
+    course,
+    semester,
+    career,
+    section,
+    archived_at,
+    deliver_at,
+    delivered_at
+  )
+  SELECT
+    user_id,
+    guild_id,
+    course,
+    semester,
+    career,
+    section,
+    $2,
+    $2,
+    CASE WHEN suspended THEN $2 END
+  FROM expired
+  RETURNING user_id, delivered_at
+)
+SELECT DISTINCT user_id AS "user_id!"
+FROM archived
+WHERE delivered_at IS NULL;
+            "#,
+            semesters,
+            now
+        )
+        .fetch_all(connection)
+        .await?
+        .into_iter()
+        .map(|x| UserId(x.user_id as u64))
+        .collect())
+    }
+
+    // notices are held for quiet hours the same as any other notification
+    pub async fn hold(
+        connection: &mut PgConnection,
+        user_id: UserId,
+        deliver_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+UPDATE archived_watchers
+SET deliver_at = $2
+WHERE
+  $1 in (user_id)
+  AND
+  delivered_at IS NULL;
+            "#,
+            user_id.0 as i64,
+            deliver_at
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+
+    // claimed the same as the outbox, so that a notice is never sent twice
+    pub async fn pending(&self) -> Result<Vec<Expired>, sqlx::Error> {
+        let now = Utc::now();
+        let mut pending = sqlx::query!(
+            r#"
+UPDATE archived_watchers
+SET claimed_until = $2
+WHERE id IN (
+  SELECT id
+  FROM archived_watchers
+  WHERE
+    delivered_at IS NULL
+    AND
+    dead_at IS NULL
+    AND
+    deliver_at <= $1
+    AND
+    (claimed_until IS NULL OR claimed_until <= $1)
+  FOR UPDATE SKIP LOCKED
+)
+RETURNING id, user_id, guild_id, course, semester, career, section;
+            "#,
+            now,
+            now + CLAIM_DURATION
+        )
+        .fetch_all(&self.database)
+        .await?;
+        pending.sort_by_key(|x| x.id);
+
+        Ok(pending
+            .into_iter()
+            .map(|rec| Expired {
+                id: rec.id,
+                recipient: Recipient {
+                    user_id: UserId(rec.user_id as u64),
+                    guild_id: rec.guild_id.map(|x| GuildId(x as u64)),
+                },
+                query: Query::from_ids(rec.course, rec.semester, rec.career, rec.section),
+            })
+            .collect())
+    }
+
+    pub async fn delivered(&self, ids: &[i64]) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE archived_watchers SET delivered_at = $1 WHERE id = ANY($2);",
+            Utc::now(),
+            ids
+        )
+        .execute(&self.database)
+        .await?;
+
+        Ok(())
+    }
+
+    // returns the notices that ran out of attempts and won't be retried
+    pub async fn retry(&self, ids: &[i64]) -> Result<Vec<i64>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+UPDATE archived_watchers
+SET
+  attempts = attempts + 1,
+  deliver_at = $1 + LEAST(INTERVAL '1 minute' * POWER(2, attempts), INTERVAL '1 hour'),
+  claimed_until = NULL,
+  dead_at = CASE WHEN attempts + 1 >= $3 THEN $1 END
+WHERE id = ANY($2)
+RETURNING id, dead_at IS NOT NULL AS "dead!";
+            "#,
+            Utc::now(),
+            ids,
+            MAX_ATTEMPTS
+        )
+        .fetch_all(&self.database)
+        .await?
+        .into_iter()
+        .filter(|x| x.dead)
+        .map(|x| x.id)
+        .collect())
+    }
+}
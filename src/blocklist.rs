@@ -0,0 +1,92 @@
+use poise::serenity_prelude::UserId;
+use sqlx::{
+    types::chrono::{DateTime, Utc},
+    PgPool,
+};
+
+#[derive(Debug)]
+pub struct Blocked {
+    pub user_id: UserId,
+    pub reason: Option<String>,
+    pub blocked_at: DateTime<Utc>,
+}
+
+// users that can't use any command or button, managed through `/admin block`
+#[derive(Debug)]
+pub struct Blocklist {
+    database: PgPool,
+}
+
+impl Blocklist {
+    pub fn new(database: PgPool) -> Blocklist {
+        Blocklist { database }
+    }
+
+    pub async fn is_blocked(&self, user_id: UserId) -> Result<bool, sqlx::Error> {
+        Ok(sqlx::query!(
+            "SELECT user_id FROM blocklist WHERE $1 in (user_id);",
+            user_id.0 as i64
+        )
+        .fetch_optional(&self.database)
+        .await?
+        .is_some())
+    }
+
+    // the user's watches are suspended so that they stop adding to the polling load
+    pub async fn block(&self, user_id: UserId, reason: Option<&str>) -> Result<(), sqlx::Error> {
+        let mut transaction = self.database.begin().await?;
+        sqlx::query!(
+            r#"
+INSERT INTO blocklist (user_id, reason)
+VALUES ($1, $2)
+ON CONFLICT (user_id) DO UPDATE
+SET reason = EXCLUDED.reason;
+            "#,
+            user_id.0 as i64,
+            reason
+        )
+        .execute(&mut *transaction)
+        .await?;
+        sqlx::query!(
+            "UPDATE watchers SET suspended = TRUE WHERE $1 in (user_id);",
+            user_id.0 as i64
+        )
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    // returns `false` if the user wasn't blocked
+    pub async fn unblock(&self, user_id: UserId) -> Result<bool, sqlx::Error> {
+        Ok(sqlx::query!(
+            "DELETE FROM blocklist WHERE $1 in (user_id);",
+            user_id.0 as i64
+        )
+        .execute(&self.database)
+        .await?
+        .rows_affected()
+            > 0)
+    }
+
+    // most recently blocked first
+    pub async fn list(&self) -> Result<Vec<Blocked>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+SELECT user_id, reason, blocked_at
+FROM blocklist
+ORDER BY blocked_at DESC;
+            "#
+        )
+        .fetch_all(&self.database)
+        .await?
+        .into_iter()
+        .map(|rec| Blocked {
+            user_id: UserId(rec.user_id as u64),
+            reason: rec.reason,
+            blocked_at: rec.blocked_at,
+        })
+        .collect())
+    }
+}
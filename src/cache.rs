@@ -1,4 +1,4 @@
-use std::{str::FromStr, time::Duration};
+use std::{str::FromStr, sync::Arc, time::Duration};
 
 use poise::serenity_prelude::futures::TryStreamExt;
 // TODO: struct that manages caching and propagating changes to watchers
@@ -7,10 +7,16 @@ use sqlx::{
         chrono::{DateTime, Utc},
         Json,
     },
-    PgPool,
+    PgConnection, PgPool,
 };
+use tracing::instrument;
 use ubs_lib::{model::ClassModel, parser::ClassSchedule, Career, Course, ParseIdError, Semester};
 
+use crate::metrics::Metrics;
+
+// undergraduate, which most classes are offered under
+pub const DEFAULT_CAREER: &str = "UGRD";
+
 // TODO: make builder
 #[derive(Debug, Clone)]
 pub struct Query {
@@ -38,6 +44,7 @@ pub enum ClassUpdate {
 #[derive(Debug)]
 pub struct Cache {
     database: PgPool,
+    metrics: Arc<Metrics>,
 }
 
 impl Query {
@@ -54,13 +61,16 @@ impl Query {
     pub fn from_raw(
         course: &str,
         semester: &str,
-        career: &str,
+        career: Option<&str>,
         section: String,
     ) -> Result<Query, ParseIdError> {
         Ok(Query::new(
-            Course::from_str(&course)?,
-            Semester::from_str(&semester)?,
-            Career::from_str(&career)?,
+            Course::from_str(course)?,
+            Semester::from_str(semester)?,
+            match career {
+                Some(career) => Career::from_str(career)?,
+                None => Career::Raw(DEFAULT_CAREER.to_owned()),
+            },
             section,
         ))
     }
@@ -81,14 +91,18 @@ impl Query {
 }
 
 impl Cache {
-    pub fn new(database: PgPool) -> Self {
-        Self { database }
+    pub fn new(database: PgPool, metrics: Arc<Metrics>) -> Self {
+        Self { database, metrics }
     }
 
     pub fn database(&self) -> &PgPool {
         &self.database
     }
 
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
     pub async fn get(&self, query: &Query) -> Result<ClassRecord, FetchClassError> {
         let latest_rec = sqlx::query!(
             r#"
@@ -118,49 +132,201 @@ ORDER BY timestamp DESC;
         })
     }
 
+    // most recent first
+    pub async fn history(
+        &self,
+        query: &Query,
+        limit: i64,
+    ) -> Result<Vec<ClassRecord>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+SELECT timestamp, data as "data: Json<ClassModel>"
+FROM cache
+WHERE
+  $1 in (course)
+  AND
+  $2 in (semester)
+  AND
+  $3 in (career)
+  AND
+  $4 in (section)
+ORDER BY timestamp DESC
+LIMIT $5;
+            "#,
+            query.course,
+            query.semester,
+            query.career,
+            query.section,
+            limit
+        )
+        .fetch_all(&self.database)
+        .await?
+        .into_iter()
+        .map(|rec| ClassRecord {
+            timestamp: rec.timestamp,
+            model: rec.data.0,
+        })
+        .collect())
+    }
+
+    // ids of every course that has been cached
+    pub async fn courses(&self) -> Result<Vec<String>, sqlx::Error> {
+        Ok(sqlx::query!("SELECT DISTINCT course FROM cache;")
+            .fetch_all(&self.database)
+            .await?
+            .into_iter()
+            .map(|x| x.course)
+            .collect())
+    }
+
+    // snapshots stored across every class
+    pub async fn rows(&self) -> Result<i64, sqlx::Error> {
+        Ok(sqlx::query!(r#"SELECT COUNT(*) AS "count!" FROM cache;"#)
+            .fetch_one(&self.database)
+            .await?
+            .count)
+    }
+
     pub async fn get_or_update(
         &self,
         query: &Query,
         max_age: Duration,
     ) -> Result<ClassUpdate, FetchClassError> {
-        let last = self.get(query).await?;
+        let update = self.get_or_fetch(query, max_age).await?;
+        if let ClassUpdate::New { new, .. } = &update {
+            Cache::insert(&mut *self.database.acquire().await?, query, new).await?;
+        }
+        Ok(update)
+    }
+
+    // same as `get_or_update` except the new record is left for the caller to insert
+    pub async fn get_or_fetch(
+        &self,
+        query: &Query,
+        max_age: Duration,
+    ) -> Result<ClassUpdate, FetchClassError> {
+        let last = match self.get(query).await {
+            Ok(last) => Some(last),
+            // the class has never been cached before
+            Err(FetchClassError::Sql(sqlx::Error::RowNotFound)) => None,
+            Err(err) => return Err(err),
+        };
 
         let now = Utc::now();
-        if now
+        match last {
+            Some(last)
+                if now
                     .signed_duration_since(last.timestamp)
                     .to_std()
                     .unwrap() // TODO
-                    > max_age
-        {
-            Ok(ClassUpdate::New {
-                old: Some(last),
+                    <= max_age =>
+            {
+                Ok(ClassUpdate::Old(last))
+            }
+            last => Ok(ClassUpdate::New {
+                old: last,
                 new: ClassRecord {
-                    model: self.update(&query).await?,
+                    model: self.fetch(query.clone()).await?,
                     timestamp: now,
                 },
-            })
-        } else {
-            Ok(ClassUpdate::Old(last))
+            }),
         }
     }
 
     pub async fn update(&self, query: &Query) -> Result<ClassModel, FetchClassError> {
-        let info = self.fetch(query.clone()).await?;
+        let record = ClassRecord {
+            model: self.fetch(query.clone()).await?,
+            timestamp: Utc::now(),
+        };
+        Cache::insert(&mut *self.database.acquire().await?, query, &record).await?;
+        Ok(record.model)
+    }
+
+    pub async fn insert(
+        connection: &mut PgConnection,
+        query: &Query,
+        record: &ClassRecord,
+    ) -> Result<(), sqlx::Error> {
         sqlx::query!(
             "INSERT INTO cache VALUES ($1, $2, $3, $4, $5, $6);",
-            Utc::now(),
+            record.timestamp,
+            query.course,
+            query.semester,
+            query.career,
+            query.section,
+            Json(&record.model) as _
+        )
+        .execute(connection)
+        .await?;
+        Ok(())
+    }
+
+    // the class isn't polled again until `at`
+    pub async fn schedule(&self, query: &Query, at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+INSERT INTO class_activity (course, semester, career, section, next_poll_at)
+VALUES ($1, $2, $3, $4, $5)
+ON CONFLICT (course, semester, career, section) DO UPDATE
+SET next_poll_at = EXCLUDED.next_poll_at;
+            "#,
             query.course,
             query.semester,
             query.career,
             query.section,
-            Json(info.clone()) as _
+            at
         )
         .execute(&self.database)
         .await?;
-        Ok(info)
+        Ok(())
     }
 
+    // the record is when the class last changed, which decides how often it's polled
+    pub async fn mark_changed(
+        connection: &mut PgConnection,
+        query: &Query,
+        record: &ClassRecord,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+INSERT INTO class_activity (course, semester, career, section, changed_at, is_open)
+VALUES ($1, $2, $3, $4, $5, $6)
+ON CONFLICT (course, semester, career, section) DO UPDATE
+SET changed_at = EXCLUDED.changed_at, is_open = EXCLUDED.is_open;
+            "#,
+            query.course,
+            query.semester,
+            query.career,
+            query.section,
+            record.timestamp,
+            record.model.is_open
+        )
+        .execute(connection)
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(
+        course = %query.course,
+        semester = %query.semester,
+        career = %query.career,
+        section = %query.section,
+    ))]
     pub async fn fetch(&self, query: Query) -> Result<ClassModel, FetchClassError> {
+        self.metrics.polls.inc();
+        let timer = self.metrics.fetch_duration.start_timer();
+        let result = self.fetch_class(query).await;
+        timer.observe_duration();
+        if let Err(err) = &result {
+            self.metrics
+                .fetch_errors
+                .with_label_values(&[err.kind()])
+                .inc();
+        }
+        result
+    }
+
+    async fn fetch_class(&self, query: Query) -> Result<ClassModel, FetchClassError> {
         let mut schedule_iter = ubs_lib::schedule_iter_with_career(
             Course::Raw(query.course),
             Semester::Raw(query.semester),
@@ -217,3 +383,16 @@ pub enum FetchClassError {
     #[error("session {0} was not found")]
     SectionNotFound(String),
 }
+
+impl FetchClassError {
+    // used to label metrics
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FetchClassError::Schedule(_) => "schedule",
+            FetchClassError::Parse(_) => "parse",
+            FetchClassError::Session(_) => "session",
+            FetchClassError::Sql(_) => "sql",
+            FetchClassError::SectionNotFound(_) => "section_not_found",
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt;
+
+use sqlx::types::chrono::{Datelike, NaiveDate, TimeZone, Utc, Weekday};
+use ubs_lib::model::ClassModel;
+
+use crate::{cache::Query, catalog, meeting::Meeting};
+
+// https://datatracker.ietf.org/doc/html/rfc5545
+const PRODUCT_ID: &str = "-//ok-nick//ubs-bot//EN";
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+// in octets, longer lines are folded onto the next
+const MAX_LINE_LENGTH: usize = 75;
+// rules for `catalog::TIMEZONE`, so that events stay put across daylight saving time
+const TIMEZONE_RULES: &[&str] = &[
+    "BEGIN:DAYLIGHT",
+    "TZOFFSETFROM:-0500",
+    "TZOFFSETTO:-0400",
+    "TZNAME:EDT",
+    "DTSTART:19700308T020000",
+    "RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=2SU",
+    "END:DAYLIGHT",
+    "BEGIN:STANDARD",
+    "TZOFFSETFROM:-0400",
+    "TZOFFSETTO:-0500",
+    "TZNAME:EST",
+    "DTSTART:19701101T020000",
+    "RRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=1SU",
+    "END:STANDARD",
+];
+
+// weekly recurring events for classes, formatted as an iCalendar file
+#[derive(Debug, Default)]
+pub struct Calendar {
+    events: Vec<Vec<String>>,
+}
+
+impl Calendar {
+    // repeats every week that the class meets between `start` and `end`, returns `false` if it
+    // doesn't meet at a set time, e.g. online classes
+    pub fn push(
+        &mut self,
+        query: &Query,
+        class: &ClassModel,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> bool {
+        let meeting = match Meeting::of(class) {
+            Some(meeting) => meeting,
+            None => return false,
+        };
+        let first = match start
+            .iter_days()
+            .take(7)
+            .find(|x| meeting.days.contains(&x.weekday()))
+        {
+            Some(first) => first,
+            None => return false,
+        };
+        // must be in utc since the start is in a timezone
+        let until = match catalog::TIMEZONE
+            .from_local_datetime(&end.and_time(meeting.end))
+            .latest()
+        {
+            Some(until) => until.with_timezone(&Utc),
+            None => return false,
+        };
+
+        let timezone = catalog::TIMEZONE.name();
+        let mut event = vec![
+            "BEGIN:VEVENT".to_owned(),
+            format!(
+                "UID:{}-{}-{}-{}@ubs-bot",
+                query.course, query.semester, query.career, query.section
+            ),
+            format!("DTSTAMP:{}Z", Utc::now().format(DATE_TIME_FORMAT)),
+            format!(
+                "DTSTART;TZID={timezone}:{}",
+                first.and_time(meeting.start).format(DATE_TIME_FORMAT)
+            ),
+            format!(
+                "DTEND;TZID={timezone}:{}",
+                first.and_time(meeting.end).format(DATE_TIME_FORMAT)
+            ),
+            format!(
+                "RRULE:FREQ=WEEKLY;BYDAY={};UNTIL={}Z",
+                meeting.days.iter().map(day).collect::<Vec<_>>().join(","),
+                until.format(DATE_TIME_FORMAT)
+            ),
+            format!("SUMMARY:{}", escape(&summary(query, class))),
+        ];
+        if let Some(room) = &class.room {
+            event.push(format!("LOCATION:{}", escape(room)));
+        }
+        if let Some(instructor) = &class.instructor {
+            event.push(format!("DESCRIPTION:{}", escape(instructor)));
+        }
+        event.push("END:VEVENT".to_owned());
+
+        self.events.push(event);
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+impl fmt::Display for Calendar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let product = format!("PRODID:{PRODUCT_ID}");
+        let timezone = format!("TZID:{}", catalog::TIMEZONE.name());
+        let lines = ["BEGIN:VCALENDAR", "VERSION:2.0", "CALSCALE:GREGORIAN"]
+            .into_iter()
+            .chain([product.as_str(), "BEGIN:VTIMEZONE", timezone.as_str()])
+            .chain(TIMEZONE_RULES.iter().copied())
+            .chain(["END:VTIMEZONE"])
+            .chain(self.events.iter().flatten().map(|x| x.as_str()))
+            .chain(["END:VCALENDAR"]);
+        for line in lines {
+            write!(f, "{}\r\n", fold(line))?;
+        }
+        Ok(())
+    }
+}
+
+// e.g. "CSE115 A1 (Lecture)"
+fn summary(query: &Query, class: &ClassModel) -> String {
+    let course = catalog::course_name(&query.course).unwrap_or(&query.course);
+    match class.class_type {
+        Some(class_type) => format!("{course} {} ({class_type})", query.section),
+        None => format!("{course} {}", query.section),
+    }
+}
+
+fn day(weekday: &Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// continuation lines start with a space, which is removed when unfolded
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_special_characters() {
+        assert_eq!(escape("Davis 101"), "Davis 101");
+        assert_eq!(escape("Smith, J; Doe\\K\nTBA"), r"Smith\, J\; Doe\\K\nTBA");
+    }
+
+    #[test]
+    fn short_lines_are_not_folded() {
+        let line = "a".repeat(MAX_LINE_LENGTH);
+        assert_eq!(fold(&line), line);
+    }
+
+    #[test]
+    fn long_lines_are_folded() {
+        let line = "a".repeat(MAX_LINE_LENGTH * 2);
+        let folded = fold(&line);
+        let lines = folded.split("\r\n").collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|x| x.len() <= MAX_LINE_LENGTH));
+        assert!(lines[1..].iter().all(|x| x.starts_with(' ')));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+
+    #[test]
+    fn folding_keeps_characters_whole() {
+        // 3 octets each
+        let line = "班".repeat(MAX_LINE_LENGTH);
+        let folded = fold(&line);
+        assert!(folded.split("\r\n").all(|x| x.len() <= MAX_LINE_LENGTH));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
@@ -0,0 +1,332 @@
+use std::{
+    collections::HashMap,
+    ops::RangeInclusive,
+    str::FromStr,
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
+
+use chrono_tz::Tz;
+use poise::serenity_prelude::futures::TryStreamExt;
+use sqlx::types::chrono::{DateTime, Datelike, NaiveDate, Utc};
+use ubs_lib::{model::ClassModel, Course, Semester};
+
+use crate::cache::FetchClassError;
+
+// times in schedules are local to UB
+pub const TIMEZONE: Tz = chrono_tz::America::New_York;
+const SCHEDULE_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+// `ubs-lib` doesn't expose a list of its mappings, so instead every course number of these subjects
+// is checked against it
+// https://catalog.buffalo.edu/courses/
+const SUBJECTS: &[&str] = &[
+    "AAS", "ACC", "AMS", "ANA", "ANT", "APY", "ARC", "ART", "ASL", "BCH", "BE", "BIO", "BMS",
+    "CDS", "CE", "CHE", "CHI", "CIE", "CL", "COM", "CSE", "DAC", "EAS", "ECO", "EE", "ELP", "END",
+    "ENG", "ES", "EVS", "FR", "GEO", "GER", "GGS", "GLY", "HIS", "IE", "ITA", "JPN", "LAI", "LAT",
+    "LIN", "MAE", "MCH", "MGA", "MGB", "MGE", "MGF", "MGG", "MGI", "MGM", "MGO", "MGQ", "MGS",
+    "MGT", "MIC", "MT", "MTH", "MUS", "NRS", "NSG", "PAS", "PHC", "PHI", "PHM", "PHY", "PSC",
+    "PSY", "PUB", "RLL", "RUS", "SOC", "SPA", "STA", "TH", "UBE",
+];
+// undergraduate and graduate courses
+const COURSE_NUMBERS: RangeInclusive<u32> = 100..=699;
+const TERMS: &[&str] = &["Spring", "Summer", "Fall", "Winter"];
+// earlier than any semester `ubs-lib` has an id for
+const FIRST_YEAR: i32 = 2020;
+// indices of spring and fall in `TERMS`, which most courses are offered during
+const MAIN_TERMS: &[usize] = &[0, 2];
+// first and last day of classes as (month, day) for each of `TERMS`, approximated from UB's academic
+// calendar since `ubs-lib` doesn't expose them
+// https://registrar.buffalo.edu/calendars/academic/
+const TERM_DATES: &[((u32, u32), (u32, u32))] = &[
+    ((1, 22), (5, 3)),
+    ((5, 28), (8, 16)),
+    ((8, 28), (12, 8)),
+    // winter session ends in the following year
+    ((12, 27), (1, 19)),
+];
+
+#[derive(Debug)]
+pub struct Schedule {
+    pub timestamp: DateTime<Utc>,
+    // classes grouped the same way as `ClassSchedule::group_iter`, e.g. a lecture with its recitations
+    pub groups: Vec<Vec<ClassModel>>,
+}
+
+#[derive(Debug)]
+pub struct SearchResult {
+    // either a name known to `ubs-lib` or a raw id
+    pub course: String,
+    pub known: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Catalog {
+    schedules: Mutex<HashMap<(String, String), Arc<Schedule>>>,
+}
+
+impl Schedule {
+    pub fn sections(&self) -> impl Iterator<Item = &str> {
+        self.groups
+            .iter()
+            .flatten()
+            .filter_map(|x| x.section.as_deref())
+    }
+
+    pub fn class(&self, section: &str) -> Option<&ClassModel> {
+        self.groups
+            .iter()
+            .flatten()
+            .find(|x| x.section.as_deref() == Some(section))
+    }
+}
+
+impl Catalog {
+    pub fn new() -> Catalog {
+        // built up front rather than during an autocomplete, which only has a few seconds
+        known_courses();
+        Catalog::default()
+    }
+
+    pub fn courses(&self) -> impl Iterator<Item = &'static str> {
+        known_courses().iter().map(|(name, _)| name.as_str())
+    }
+
+    // matches against known course names, as well as the ids of any course that has been cached
+    // but isn't known by name. Titles aren't matched, neither of them include one, so "data
+    // structures" won't find CSE250
+    pub fn search(&self, query: &str, seen_ids: &[String]) -> Vec<SearchResult> {
+        let query = normalize(query);
+
+        let known = known_courses();
+        let mut results = known
+            .iter()
+            .map(|(name, _)| SearchResult {
+                course: name.to_owned(),
+                known: true,
+            })
+            .chain(
+                seen_ids
+                    .iter()
+                    .chain(self.schedules.lock().unwrap().keys().map(|(id, _)| id))
+                    .filter(|id| !known.iter().any(|(_, known_id)| known_id == *id))
+                    .map(|id| SearchResult {
+                        course: id.to_owned(),
+                        known: false,
+                    }),
+            )
+            .filter_map(|result| {
+                let name = normalize(&result.course);
+                let rank = if name.starts_with(&query) {
+                    0
+                } else if name.contains(&query) {
+                    1
+                } else {
+                    return None;
+                };
+                Some((rank, result))
+            })
+            .collect::<Vec<_>>();
+
+        results.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.course.cmp(&b.1.course)));
+        results.dedup_by(|a, b| a.1.course == b.1.course);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    pub fn cached(&self, course: &Course, semester: &Semester) -> Option<Arc<Schedule>> {
+        self.schedules
+            .lock()
+            .unwrap()
+            .get(&(course.id().to_owned(), semester.id().to_owned()))
+            .filter(|x| {
+                Utc::now()
+                    .signed_duration_since(x.timestamp)
+                    .to_std()
+                    .unwrap_or_default()
+                    <= SCHEDULE_MAX_AGE
+            })
+            .cloned()
+    }
+
+    pub async fn schedule(
+        &self,
+        course: Course,
+        semester: Semester,
+    ) -> Result<Arc<Schedule>, FetchClassError> {
+        if let Some(schedule) = self.cached(&course, &semester) {
+            return Ok(schedule);
+        }
+
+        let key = (course.id().to_owned(), semester.id().to_owned());
+        let mut groups = Vec::new();
+        let mut schedule_iter = ubs_lib::schedule_iter(course, semester).await?;
+        if let Some(schedule) = schedule_iter.try_next().await? {
+            let schedule = schedule?;
+            for group in schedule.group_iter() {
+                groups.push(
+                    group
+                        .class_iter()
+                        .map(|x| x.model())
+                        .collect::<Result<Vec<_>, _>>()?,
+                );
+            }
+        }
+
+        let schedule = Arc::new(Schedule {
+            timestamp: Utc::now(),
+            groups,
+        });
+        self.schedules.lock().unwrap().insert(key, schedule.clone());
+        Ok(schedule)
+    }
+}
+
+pub fn course_name(id: &str) -> Option<&'static str> {
+    known_courses()
+        .iter()
+        .find(|(_, known_id)| known_id == id)
+        .map(|(name, _)| name.as_str())
+}
+
+// names of every semester from the last year up to two years ahead that `ubs-lib` knows about,
+// in order
+pub fn known_semesters() -> impl Iterator<Item = String> {
+    let year = Utc::now().year();
+    (0..TERMS.len() * 3)
+        .map(move |i| semester(year - 1, i))
+        .filter(|x| Semester::from_str(x).is_ok())
+}
+
+// looks through every year since `FIRST_YEAR` rather than only the known semesters, watches of
+// older semesters still need their dates to be archived
+pub fn semester_name(id: &str) -> Option<String> {
+    let years = (Utc::now().year() + 2 - FIRST_YEAR).max(0) as usize;
+    (0..TERMS.len() * years)
+        .map(|i| semester(FIRST_YEAR, i))
+        .find(|x| Semester::from_str(x).map_or(false, |x| x.id() == id))
+}
+
+// spring or fall, as opposed to a summer or winter session
+pub fn is_main_term(name: &str) -> bool {
+    parse_semester(name).map_or(false, |(term, _)| MAIN_TERMS.contains(&term))
+}
+
+// first and last day of classes
+pub fn semester_dates(id: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let (term, year) = parse_semester(&semester_name(id)?)?;
+    let ((start_month, start_day), (end_month, end_day)) = TERM_DATES[term];
+    let end_year = if end_month < start_month {
+        year + 1
+    } else {
+        year
+    };
+    Some((
+        NaiveDate::from_ymd_opt(year, start_month, start_day)?,
+        NaiveDate::from_ymd_opt(end_year, end_month, end_day)?,
+    ))
+}
+
+// the fall or spring semester after `id`, where a course is most likely offered again
+pub fn next_semester(id: &str) -> Option<Semester> {
+    let (term, year) = parse_semester(&semester_name(id)?)?;
+    (term + 1..term + 1 + TERMS.len())
+        .filter(|i| MAIN_TERMS.contains(&(i % TERMS.len())))
+        .find_map(|i| Semester::from_str(&semester(year, i)).ok())
+}
+
+// splits a name such as "Fall2023" into the index of its term and its year
+pub fn parse_semester(name: &str) -> Option<(usize, i32)> {
+    TERMS.iter().enumerate().find_map(|(i, term)| {
+        name.strip_prefix(term)
+            .and_then(|year| year.parse().ok())
+            .map(|year| (i, year))
+    })
+}
+
+// name and id of every course `ubs-lib` knows about, only checked once since the mappings are
+// compiled into it. That's a `Course::from_str` for each of the ~46k combinations of `SUBJECTS`
+// and `COURSE_NUMBERS`, and a subject missing from the hand-copied `SUBJECTS` is silently left
+// out, its courses can still be found by their raw id once cached
+fn known_courses() -> &'static [(String, String)] {
+    static COURSES: OnceLock<Vec<(String, String)>> = OnceLock::new();
+    COURSES.get_or_init(|| {
+        SUBJECTS
+            .iter()
+            .flat_map(|subject| COURSE_NUMBERS.map(move |number| format!("{subject}{number}")))
+            .filter_map(|name| {
+                let id = Course::from_str(&name).ok()?.id().to_owned();
+                Some((name, id))
+            })
+            .collect()
+    })
+}
+
+// the `i`th term counting from the start of `year`
+fn semester(year: i32, i: usize) -> String {
+    format!(
+        "{}{}",
+        TERMS[i % TERMS.len()],
+        year + (i / TERMS.len()) as i32
+    )
+}
+
+// ignores case and whitespace, so that "cse 25" matches "CSE250"
+pub fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|x| !x.is_whitespace())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unknown(results: &[SearchResult]) -> Vec<&str> {
+        results
+            .iter()
+            .filter(|x| !x.known)
+            .map(|x| x.course.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn known_courses_resolve() {
+        let id = Course::from_str("CSE115").unwrap().id().to_owned();
+        assert_eq!(course_name(&id), Some("CSE115"));
+    }
+
+    #[test]
+    fn search_ignores_case_and_whitespace() {
+        let results = Catalog::default().search("cse 11", &[]);
+        assert!(results.iter().any(|x| x.course == "CSE115" && x.known));
+        assert!(results
+            .iter()
+            .all(|x| normalize(&x.course).contains("cse11")));
+    }
+
+    #[test]
+    fn search_includes_unknown_ids() {
+        let seen = ["RAW001".to_owned(), "RAW002".to_owned()];
+        let results = Catalog::default().search("raw001", &seen);
+        assert_eq!(unknown(&results), vec!["RAW001"]);
+    }
+
+    #[test]
+    fn search_ranks_prefixes_first() {
+        let seen = [
+            "ZRAW01".to_owned(),
+            "RAW001".to_owned(),
+            "RAW001".to_owned(),
+        ];
+        let results = Catalog::default().search("raw", &seen);
+        // duplicates are only listed once
+        assert_eq!(unknown(&results), vec!["RAW001", "ZRAW01"]);
+    }
+
+    #[test]
+    fn search_without_matches() {
+        assert!(Catalog::default()
+            .search("not a course", &["RAW001".to_owned()])
+            .is_empty());
+    }
+}
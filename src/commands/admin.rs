@@ -0,0 +1,289 @@
+use std::time::Duration;
+
+use poise::serenity_prelude::User;
+use sqlx::types::chrono::{self, Utc};
+
+use super::{autocomplete, defaults};
+use crate::{
+    cache::Query,
+    render::{self, describe},
+    translation::{tr, Locale},
+    watcher::Check,
+    Context,
+};
+
+const TOP_WATCHED: i64 = 10;
+
+// owners are configured through `OWNERS`, the commands are hidden from everyone else
+#[poise::command(
+    slash_command,
+    owners_only,
+    default_member_permissions = "ADMINISTRATOR",
+    subcommands(
+        "status",
+        "pause",
+        "resume",
+        "force_check",
+        "stats",
+        "purge_user",
+        "block",
+        "unblock",
+        "blocklist"
+    )
+)]
+pub async fn admin(_ctx: Context<'_>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+// #[description("Show the health of the poll loop")]
+#[poise::command(slash_command, owners_only)]
+pub async fn status(ctx: Context<'_>) -> Result<(), crate::Error> {
+    ctx.defer_ephemeral().await?;
+
+    let locale = Locale::from_ctx(ctx);
+    let watcher = &ctx.data().watcher;
+    let mut lines = vec![if watcher.is_paused() {
+        tr!(locale, "admin-status-paused")
+    } else {
+        tr!(locale, "admin-status-running")
+    }];
+    match watcher.last_cycle() {
+        Some(cycle) => {
+            let finished = Utc::now()
+                - chrono::Duration::from_std(cycle.finished.elapsed())
+                    .unwrap_or_else(|_| chrono::Duration::zero());
+            lines.push(tr!(
+                locale,
+                "admin-status-cycle",
+                finished: format!("<t:{}:R>", finished.timestamp()),
+                duration: format!("{:.1}", cycle.duration.as_secs_f64()),
+                checked: cycle.checked
+            ));
+            lines.push(tr!(
+                locale,
+                "admin-status-errors",
+                errors: cycle.errors,
+                rate: format!(
+                    "{:.1}",
+                    cycle.errors as f64 / cycle.checked.max(1) as f64 * 100.0
+                )
+            ));
+        }
+        None => lines.push(tr!(locale, "admin-status-no-cycle")),
+    }
+    lines.push(tr!(
+        locale,
+        "admin-status-backlog",
+        count: watcher.outbox().backlog().await?
+    ));
+
+    ctx.say(lines.join("\n")).await?;
+
+    Ok(())
+}
+
+// #[description("Stop polling classes")]
+#[poise::command(slash_command, owners_only)]
+pub async fn pause(ctx: Context<'_>) -> Result<(), crate::Error> {
+    ctx.data().watcher.set_paused(true);
+    ctx.send(|f| {
+        f.content(tr!(Locale::from_ctx(ctx), "admin-paused"))
+            .ephemeral(true)
+    })
+    .await?;
+    Ok(())
+}
+
+// #[description("Resume polling classes")]
+#[poise::command(slash_command, owners_only)]
+pub async fn resume(ctx: Context<'_>) -> Result<(), crate::Error> {
+    ctx.data().watcher.set_paused(false);
+    ctx.send(|f| {
+        f.content(tr!(Locale::from_ctx(ctx), "admin-resumed"))
+            .ephemeral(true)
+    })
+    .await?;
+    Ok(())
+}
+
+// #[description("Fetch a class from UB now, notifying its watchers of any change")]
+#[poise::command(slash_command, owners_only, rename = "force-check")]
+pub async fn force_check(
+    ctx: Context<'_>,
+    #[description = "Course name, e.g. CSE115"]
+    #[autocomplete = "autocomplete::course"]
+    course: String,
+    #[description = "Section code, e.g. A1"]
+    #[autocomplete = "autocomplete::section"]
+    section: String,
+    #[description = "Semester name, e.g. Fall2023, defaults to your preference or the upcoming semester"]
+    #[autocomplete = "autocomplete::semester"]
+    semester: Option<String>,
+    #[description = "Career the course is offered under, defaults to undergraduate"] career: Option<
+        String,
+    >,
+) -> Result<(), crate::Error> {
+    ctx.defer_ephemeral().await?;
+
+    let semester = defaults::semester(ctx, semester).await?;
+
+    let query = Query::from_raw(
+        &course,
+        &semester,
+        career.as_deref(),
+        section.to_uppercase(),
+    )?;
+    // goes through the watcher so that any change is delivered as usual
+    let record = match ctx
+        .data()
+        .watcher
+        .check(query.clone(), Duration::ZERO)
+        .await?
+    {
+        Check::Old(record) => record,
+        Check::New(notifier) => notifier.into_new_record(),
+    };
+
+    let locale = Locale::from_ctx(ctx);
+    ctx.send(|f| f.embed(|e| render::class_embed(e, locale, &query, &record)))
+        .await?;
+
+    Ok(())
+}
+
+// #[description("Show how many classes are watched")]
+#[poise::command(slash_command, owners_only)]
+pub async fn stats(ctx: Context<'_>) -> Result<(), crate::Error> {
+    ctx.defer_ephemeral().await?;
+
+    let locale = Locale::from_ctx(ctx);
+    let watcher = &ctx.data().watcher;
+    let stats = watcher.stats().await?;
+    let top = watcher
+        .top_watched(TOP_WATCHED)
+        .await?
+        .iter()
+        .enumerate()
+        .map(|(i, (query, watchers))| {
+            tr!(
+                locale,
+                "admin-stats-entry",
+                rank: i + 1,
+                class: describe(locale, query),
+                count: *watchers
+            )
+        })
+        .collect::<Vec<_>>();
+
+    ctx.say(format!(
+        "{}\n\n{}",
+        tr!(
+            locale,
+            "admin-stats-summary",
+            watches: stats.watches,
+            suspended: stats.suspended,
+            users: stats.users,
+            classes: stats.classes
+        ),
+        if top.is_empty() {
+            tr!(locale, "admin-stats-empty")
+        } else {
+            top.join("\n")
+        }
+    ))
+    .await?;
+
+    Ok(())
+}
+
+// #[description("Delete every watch, notification and setting of a user")]
+#[poise::command(slash_command, owners_only, rename = "purge-user")]
+pub async fn purge_user(
+    ctx: Context<'_>,
+    #[description = "User to purge"] target: User,
+) -> Result<(), crate::Error> {
+    ctx.defer_ephemeral().await?;
+
+    let watches = ctx.data().watcher.purge_user(target.id).await?;
+    ctx.say(tr!(
+        Locale::from_ctx(ctx),
+        "admin-purged",
+        user: target.tag(),
+        count: watches
+    ))
+    .await?;
+
+    Ok(())
+}
+
+// #[description("Stop a user from using the bot and suspend their watches")]
+#[poise::command(slash_command, owners_only)]
+pub async fn block(
+    ctx: Context<'_>,
+    #[description = "User to block"] target: User,
+    #[description = "Why the user is blocked, only shown to admins"] reason: Option<String>,
+) -> Result<(), crate::Error> {
+    ctx.defer_ephemeral().await?;
+
+    ctx.data()
+        .blocklist
+        .block(target.id, reason.as_deref())
+        .await?;
+    ctx.say(tr!(Locale::from_ctx(ctx), "admin-blocked", user: target.tag()))
+        .await?;
+
+    Ok(())
+}
+
+// #[description("Let a blocked user use the bot again")]
+#[poise::command(slash_command, owners_only)]
+pub async fn unblock(
+    ctx: Context<'_>,
+    #[description = "User to unblock"] target: User,
+) -> Result<(), crate::Error> {
+    ctx.defer_ephemeral().await?;
+
+    // their watches stay suspended, they can watch the classes again if they still want them
+    let locale = Locale::from_ctx(ctx);
+    ctx.say(if ctx.data().blocklist.unblock(target.id).await? {
+        tr!(locale, "admin-unblocked", user: target.tag())
+    } else {
+        tr!(locale, "admin-not-blocked", user: target.tag())
+    })
+    .await?;
+
+    Ok(())
+}
+
+// #[description("Show every blocked user")]
+#[poise::command(slash_command, owners_only)]
+pub async fn blocklist(ctx: Context<'_>) -> Result<(), crate::Error> {
+    ctx.defer_ephemeral().await?;
+
+    let locale = Locale::from_ctx(ctx);
+    let entries = ctx
+        .data()
+        .blocklist
+        .list()
+        .await?
+        .iter()
+        .map(|x| {
+            tr!(
+                locale,
+                "admin-blocklist-entry",
+                user: format!("<@{}>", x.user_id),
+                blocked: format!("<t:{}:R>", x.blocked_at.timestamp()),
+                reason: x.reason.clone().unwrap_or_else(|| tr!(locale, "unknown"))
+            )
+        })
+        .collect::<Vec<_>>();
+
+    ctx.say(if entries.is_empty() {
+        tr!(locale, "admin-blocklist-empty")
+    } else {
+        entries.join("\n")
+    })
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use ubs_lib::{Course, Semester};
+
+use super::defaults;
+use crate::{academic, catalog::normalize, Context};
+
+// https://discord.com/developers/docs/interactions/application-commands#application-command-object-application-command-option-structure
+const MAX_CHOICES: usize = 25;
+
+pub async fn course(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+    let partial = normalize(partial);
+    ctx.data()
+        .catalog
+        .courses()
+        .filter(|x| normalize(x).contains(&partial))
+        .take(MAX_CHOICES)
+        .map(|x| x.to_owned())
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+pub async fn semester(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+    let partial = normalize(partial);
+    ctx.data()
+        .calendar
+        .semesters(academic::today())
+        .into_iter()
+        .filter(move |x| normalize(x).contains(&partial))
+        .take(MAX_CHOICES)
+}
+
+// only possible once the course is chosen, the semester falls back to the same default as the
+// command. Only schedules that are already cached are used, otherwise every keystroke could
+// fetch from UB
+pub async fn section(ctx: Context<'_>, partial: &str) -> impl Iterator<Item = String> {
+    let partial = partial.to_uppercase();
+    let course = match option(ctx, "course") {
+        Some(course) => course,
+        None => return Vec::new().into_iter(),
+    };
+    let semester = match defaults::semester(ctx, option(ctx, "semester")).await {
+        Ok(semester) => semester,
+        Err(_) => return Vec::new().into_iter(),
+    };
+    let (course, semester) = match (course.parse::<Course>(), semester.parse::<Semester>()) {
+        (Ok(course), Ok(semester)) => (course, semester),
+        _ => return Vec::new().into_iter(),
+    };
+
+    let schedule = match ctx.data().catalog.cached(&course, &semester) {
+        Some(schedule) => schedule,
+        None => return Vec::new().into_iter(),
+    };
+
+    schedule
+        .sections()
+        .filter(|x| x.contains(&partial))
+        .take(MAX_CHOICES)
+        .map(|x| x.to_owned())
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+fn option(ctx: Context<'_>, name: &str) -> Option<String> {
+    match ctx {
+        poise::Context::Application(ctx) => ctx
+            .interaction
+            .data()
+            .options
+            .iter()
+            .find(|x| x.name == name)
+            .and_then(|x| x.value.as_ref())
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_owned()),
+        poise::Context::Prefix(_) => None,
+    }
+}
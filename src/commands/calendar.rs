@@ -0,0 +1,95 @@
+use std::borrow::Cow;
+
+use poise::serenity_prelude::AttachmentType;
+use ubs_lib::Semester;
+
+use super::{autocomplete, defaults, sections};
+use crate::{
+    calendar::Calendar,
+    catalog,
+    translation::{tr, Locale},
+    Context,
+};
+
+// #[description("Export classes as a calendar file")]
+#[poise::command(slash_command)]
+pub async fn calendar(
+    ctx: Context<'_>,
+    #[description = "Semester name, e.g. Fall2023, defaults to your preference or the upcoming semester"]
+    #[autocomplete = "autocomplete::semester"]
+    semester: Option<String>,
+    #[description = "Course and section pairs, e.g. \"CSE115 A1, MTH141 B2\", defaults to your watches"]
+    sections: Option<String>,
+) -> Result<(), crate::Error> {
+    ctx.defer_ephemeral().await?;
+
+    let semester = defaults::semester(ctx, semester).await?;
+
+    let locale = Locale::from_ctx(ctx);
+    let semester_id = semester.parse::<Semester>()?.id().to_owned();
+    let (start, end) = match catalog::semester_dates(&semester_id) {
+        Some(dates) => dates,
+        None => {
+            ctx.say(tr!(locale, "calendar-unknown-semester", semester: semester.as_str()))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let queries = match sections {
+        Some(sections) => match sections::parse(&sections, &semester)? {
+            Some(queries) => queries,
+            None => {
+                ctx.say(tr!(locale, "sections-invalid")).await?;
+                return Ok(());
+            }
+        },
+        None => ctx
+            .data()
+            .watcher
+            .watches(ctx.author().id)
+            .await?
+            .into_iter()
+            .filter(|x| x.semester == semester_id)
+            .collect(),
+    };
+    if queries.is_empty() {
+        ctx.say(tr!(locale, "sections-empty", semester: semester.as_str()))
+            .await?;
+        return Ok(());
+    }
+
+    let (classes, mut missing) = sections::classes(ctx, queries).await;
+    let mut calendar = Calendar::default();
+    for (query, class) in classes {
+        if !calendar.push(&query, &class, start, end) {
+            missing.push(query);
+        }
+    }
+
+    let mut content = Vec::new();
+    if !calendar.is_empty() {
+        content.push(tr!(locale, "calendar-created", count: calendar.len()));
+    }
+    if !missing.is_empty() {
+        content.push(tr!(
+            locale,
+            "calendar-missing",
+            classes: sections::list(locale, &missing)
+        ));
+    }
+
+    ctx.send(|f| {
+        f.content(content.join("\n\n"));
+        if !calendar.is_empty() {
+            f.attachment(AttachmentType::Bytes {
+                data: Cow::Owned(calendar.to_string().into_bytes()),
+                filename: format!("{semester}.ics"),
+            });
+        }
+        f
+    })
+    .await?;
+
+    Ok(())
+}
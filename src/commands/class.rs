@@ -1,23 +1,42 @@
 use poise::serenity_prelude::futures::TryStreamExt;
 use poise::CreateReply;
+use sqlx::types::chrono::Utc;
+use tracing::error;
 use ubs_lib::{model::ClassModel, parser::ClassSchedule, Course, Semester};
 
-use crate::{cache::Query, watcher::Check, Context, MAX_AGE};
-
-const TIME_FORMAT: &str = "%-I:%M%p";
-const UNKNOWN_FIELD: &str = "[unknown]";
+use super::{autocomplete, conflicts, defaults, sections};
+use crate::{
+    cache::{ClassRecord, Query, DEFAULT_CAREER},
+    expiry::{self, Expiry},
+    interactions,
+    notifier::Recipient,
+    render::{self, describe},
+    translation::{tr, Locale},
+    watcher::Check,
+    Context, MAX_AGE,
+};
 
 // #[description("Get information of class")]
 #[poise::command(slash_command)]
 pub async fn info(
     ctx: Context<'_>,
-    #[description = "test"] course: String,
-    #[description = "test"] semester: String,
-    #[description = "test"] section: String,
-    #[description = "test"] career: Option<String>,
+    #[description = "Course name, e.g. CSE115"]
+    #[autocomplete = "autocomplete::course"]
+    course: String,
+    #[description = "Section code, e.g. A1"]
+    #[autocomplete = "autocomplete::section"]
+    section: String,
+    #[description = "Semester name, e.g. Fall2023, defaults to your preference or the upcoming semester"]
+    #[autocomplete = "autocomplete::semester"]
+    semester: Option<String>,
+    #[description = "Career the course is offered under, defaults to undergraduate"] career: Option<
+        String,
+    >,
 ) -> Result<(), crate::Error> {
     ctx.defer().await?;
 
+    let semester = defaults::semester(ctx, semester).await?;
+
     let section = section.to_uppercase();
 
     // TODO: create function to try infer career in query
@@ -38,19 +57,25 @@ pub async fn info(
     //     }
     // }
 
+    let query = Query::from_raw(&course, &semester, career.as_deref(), section.clone())?;
     match fetch_class_info(course.parse()?, semester.parse()?, &section).await? {
         Some(class) => {
-            ctx.send(|f| info_msg(f, &class, &course, &semester))
+            let record = ClassRecord {
+                timestamp: Utc::now(),
+                model: class,
+            };
+            ctx.send(|f| info_msg(f, Locale::from_ctx(ctx), &query, &record))
                 .await?;
         }
         None => {
             // TODO: use embeds
-            ctx.say(format!("Could not find {course}, section {section}, during {semester}.
-
-*Does this class exist? It may just be missing a mapping.
-Read here for more information: https://github.com/ok-nick/ubs-bot#why-cant-it-find-a-class-that-i-know-exists*"),
-                )
-                .await?;
+            let locale = Locale::from_ctx(ctx);
+            ctx.say(format!(
+                "{}\n\n*{}*",
+                tr!(locale, "info-not-found", class: describe(locale, &query)),
+                tr!(locale, "info-missing-mapping")
+            ))
+            .await?;
         }
     }
 
@@ -64,10 +89,10 @@ Read here for more information: https://github.com/ok-nick/ubs-bot#why-cant-it-f
 #[poise::command(slash_command)]
 pub async fn rawinfo(
     ctx: Context<'_>,
-    #[description = "test"] course: String,
-    #[description = "test"] semester: String,
-    #[description = "test"] section: String,
-    #[description = "test"] career: Option<String>,
+    #[description = "Course id"] course: String,
+    #[description = "Semester id"] semester: String,
+    #[description = "Section code, e.g. A1"] section: String,
+    #[description = "Career id"] career: Option<String>,
 ) -> Result<(), crate::Error> {
     ctx.defer().await?;
 
@@ -83,14 +108,28 @@ pub async fn rawinfo(
     .await?
     {
         Some(class) => {
-            ctx.send(|f| info_msg(f, &class, &course, &semester))
+            let query = Query::from_ids(
+                course.clone(),
+                semester.clone(),
+                career.unwrap_or(DEFAULT_CAREER.to_owned()),
+                section,
+            );
+            let record = ClassRecord {
+                timestamp: Utc::now(),
+                model: class,
+            };
+            ctx.send(|f| info_msg(f, Locale::from_ctx(ctx), &query, &record))
                 .await?;
         }
         None => {
-            ctx.say(
-                    format!("Could not find course id {course}, section {section}, during semester id {semester}."),
-                )
-                .await?;
+            ctx.say(tr!(
+                Locale::from_ctx(ctx),
+                "rawinfo-not-found",
+                course: course,
+                section: section,
+                semester: semester
+            ))
+            .await?;
         }
     }
 
@@ -101,26 +140,106 @@ pub async fn rawinfo(
 #[poise::command(slash_command)]
 pub async fn watch(
     ctx: Context<'_>,
-    #[description = "test"] course: String,
-    #[description = "test"] semester: String,
-    #[description = "test"] section: String,
-    #[description = "test"] career: Option<String>,
+    #[description = "Course name, e.g. CSE115"]
+    #[autocomplete = "autocomplete::course"]
+    course: String,
+    #[description = "Section code, e.g. A1"]
+    #[autocomplete = "autocomplete::section"]
+    section: String,
+    #[description = "Semester name, e.g. Fall2023, defaults to your preference or the upcoming semester"]
+    #[autocomplete = "autocomplete::semester"]
+    semester: Option<String>,
+    #[description = "Career the course is offered under, defaults to undergraduate"] career: Option<
+        String,
+    >,
+    #[description = "When to stop watching, e.g. \"for 3 days\", \"until Friday\" or \"after first notification\""]
+    expires: Option<String>,
 ) -> Result<(), crate::Error> {
     ctx.defer().await?;
 
-    let section = section.to_uppercase();
+    let semester = defaults::semester(ctx, semester).await?;
 
-    // TODO: move all queries to a Database struct
-    sqlx::query!(
-        "INSERT INTO watchers VALUES ($1, $2, $3, $4, $5)",
-        ctx.author().id.0 as i64,
-        course,
-        semester,
-        career,
-        section
-    )
-    .execute(ctx.data().watcher.cache().database())
-    .await?;
+    let query = Query::from_raw(&course, &semester, career.as_deref(), section)?;
+    // dates are in the user's own timezone
+    let expiry = match &expires {
+        Some(expires) => {
+            let timezone = ctx
+                .data()
+                .watcher
+                .settings()
+                .schedule(ctx.author().id)
+                .await?
+                .timezone;
+            match expiry::parse_expiry(expires, Utc::now(), timezone) {
+                Some(expiry) => Some(expiry),
+                None => {
+                    ctx.say(tr!(Locale::from_ctx(ctx), "expiry-invalid"))
+                        .await?;
+                    return Ok(());
+                }
+            }
+        }
+        None => None,
+    };
+    let added = ctx
+        .data()
+        .watcher
+        .add_watch(
+            Recipient {
+                user_id: ctx.author().id,
+                guild_id: ctx.guild_id(),
+            },
+            &query,
+            expiry,
+        )
+        .await;
+    if let Err(err) = added {
+        let content = render::watch_error(Locale::from_ctx(ctx), &err).ok_or(err)?;
+        ctx.say(content).await?;
+        return Ok(());
+    }
+    if let Some(locale) = ctx.locale() {
+        ctx.data()
+            .watcher
+            .settings()
+            .set_locale(ctx.author().id, locale)
+            .await?;
+    }
+
+    let conflicts = match conflicts::watch_conflicts(ctx, &query).await {
+        Ok(conflicts) => conflicts,
+        Err(err) => {
+            error!("failed to check conflicts of {:?}: {:?}", query, err);
+            Vec::new()
+        }
+    };
+
+    let locale = Locale::from_ctx(ctx);
+    let mut content = tr!(locale, "watch-added", class: describe(locale, &query));
+    match expiry {
+        Some(Expiry::At(expires_at)) => {
+            content.push('\n');
+            content.push_str(&tr!(
+                locale,
+                "watch-expires",
+                time: format!("<t:{}:f>", expires_at.timestamp())
+            ));
+        }
+        Some(Expiry::FirstNotification) => {
+            content.push('\n');
+            content.push_str(&tr!(locale, "watch-once"));
+        }
+        None => {}
+    }
+    if !conflicts.is_empty() {
+        content.push_str("\n\n");
+        content.push_str(&tr!(
+            locale,
+            "watch-conflicts",
+            classes: sections::list(locale, &conflicts)
+        ));
+    }
+    ctx.say(content).await?;
 
     Ok(())
 }
@@ -130,103 +249,106 @@ pub async fn watch(
 #[poise::command(slash_command)]
 pub async fn unwatch(
     ctx: Context<'_>,
-    #[description = "test"] course: String,
-    #[description = "test"] semester: String,
-    #[description = "test"] section: String,
-    #[description = "test"] career: Option<String>,
+    #[description = "Course name, e.g. CSE115"]
+    #[autocomplete = "autocomplete::course"]
+    course: String,
+    #[description = "Section code, e.g. A1"]
+    #[autocomplete = "autocomplete::section"]
+    section: String,
+    #[description = "Semester name, e.g. Fall2023, defaults to your preference or the upcoming semester"]
+    #[autocomplete = "autocomplete::semester"]
+    semester: Option<String>,
+    #[description = "Career the course is offered under, defaults to undergraduate"] career: Option<
+        String,
+    >,
 ) -> Result<(), crate::Error> {
     ctx.defer().await?;
 
-    let section = section.to_uppercase();
+    let semester = defaults::semester(ctx, semester).await?;
 
-    todo!()
+    let query = Query::from_raw(&course, &semester, career.as_deref(), section)?;
+    let locale = Locale::from_ctx(ctx);
+    if ctx
+        .data()
+        .watcher
+        .remove_watch(ctx.author().id, &query)
+        .await?
+    {
+        ctx.say(tr!(locale, "watch-removed", class: describe(locale, &query)))
+            .await?;
+    } else {
+        ctx.say(tr!(locale, "watch-missing", class: describe(locale, &query)))
+            .await?;
+    }
+
+    Ok(())
+}
+
+// #[description("Silence notifications for a watched class for a while")]
+#[poise::command(slash_command)]
+pub async fn snooze(
+    ctx: Context<'_>,
+    #[description = "Course name, e.g. CSE115"]
+    #[autocomplete = "autocomplete::course"]
+    course: String,
+    #[description = "Section code, e.g. A1"]
+    #[autocomplete = "autocomplete::section"]
+    section: String,
+    #[description = "How long to silence the watch, e.g. \"3 days\" or \"12 hours\""]
+    duration: String,
+    #[description = "Semester name, e.g. Fall2023, defaults to your preference or the upcoming semester"]
+    #[autocomplete = "autocomplete::semester"]
+    semester: Option<String>,
+    #[description = "Career the course is offered under, defaults to undergraduate"] career: Option<
+        String,
+    >,
+) -> Result<(), crate::Error> {
+    ctx.defer().await?;
+
+    let semester = defaults::semester(ctx, semester).await?;
+
+    let query = Query::from_raw(&course, &semester, career.as_deref(), section)?;
+    let locale = Locale::from_ctx(ctx);
+    let until = match expiry::parse_duration(&duration) {
+        Some(duration) => Utc::now() + duration,
+        None => {
+            ctx.say(tr!(locale, "snooze-invalid")).await?;
+            return Ok(());
+        }
+    };
+
+    // changes while snoozed are still recorded, they just aren't delivered
+    if ctx
+        .data()
+        .watcher
+        .snooze(ctx.author().id, &query, until)
+        .await?
+    {
+        ctx.say(tr!(
+            locale,
+            "snooze-set",
+            class: describe(locale, &query),
+            time: format!("<t:{}:f>", until.timestamp())
+        ))
+        .await?;
+    } else {
+        ctx.say(tr!(locale, "watch-missing", class: describe(locale, &query)))
+            .await?;
+    }
+
+    Ok(())
 }
 
-// TODO: absolute monstrosity of a function
 fn info_msg<'a, 'b>(
     f: &'a mut CreateReply<'b>,
-    class: &ClassModel,
-    course: &str,
-    semester: &str,
+    locale: Locale,
+    query: &Query,
+    record: &ClassRecord,
 ) -> &'a mut CreateReply<'b> {
-    f.embed(|e| {
-        // TODO: set timestamp of embed to last time data was updated. so if the data was taken from a cache
-        //       you know when it was last updated
-        e.title(format!("{} - {}", course, semester))
-            .author(|a| a.name(class.instructor.as_deref().unwrap_or(UNKNOWN_FIELD)))
-            // .field("Id", class.class_id.unwrap(), true)
-            .field(
-                "Section",
-                class.section.as_deref().unwrap_or(UNKNOWN_FIELD),
-                true,
-            )
-            .field(
-                "Type",
-                class
-                    .class_type
-                    .map(|x| x.to_string())
-                    .as_deref()
-                    .unwrap_or(UNKNOWN_FIELD),
-                true,
-            )
-            .field("Room", class.room.as_deref().unwrap_or(UNKNOWN_FIELD), true)
-            .field(
-                "Open",
-                class
-                    .is_open
-                    .map(|x| x.to_string())
-                    .as_deref()
-                    .unwrap_or(UNKNOWN_FIELD),
-                true,
-            )
-            .field(
-                "Seats",
-                format!(
-                    "{}/{}",
-                    class
-                        .open_seats
-                        .map(|x| x.to_string())
-                        .as_deref()
-                        .unwrap_or(UNKNOWN_FIELD),
-                    class
-                        .total_seats
-                        .map(|x| x.to_string())
-                        .as_deref()
-                        .unwrap_or(UNKNOWN_FIELD),
-                ),
-                true,
-            )
-            .field(
-                // TODO: dynamically set plurality
-                "Day(s) of Week",
-                match class.days_of_week {
-                    Some(ref dow) => dow
-                        .iter()
-                        .map(|x| x.map(|y| y.to_string()).unwrap_or(UNKNOWN_FIELD.to_owned()))
-                        .collect::<Vec<String>>()
-                        .join(", "),
-                    None => UNKNOWN_FIELD.to_owned(),
-                },
-                false,
-            )
-            .field(
-                "Time",
-                format!(
-                    "{} — {}",
-                    class
-                        .start_time
-                        .map(|x| x.format(TIME_FORMAT).to_string())
-                        .as_deref()
-                        .unwrap_or(UNKNOWN_FIELD),
-                    class
-                        .end_time
-                        .map(|x| x.format(TIME_FORMAT).to_string())
-                        .as_deref()
-                        .unwrap_or(UNKNOWN_FIELD),
-                ),
-                true,
-            )
-    })
+    f.embed(|e| render::class_embed(e, locale, query, record))
+        .components(|c| {
+            c.create_action_row(|r| interactions::class_buttons(r, locale, query, None))
+        })
 }
 
 // TODO: cache schedules
@@ -0,0 +1,22 @@
+use poise::serenity_prelude::{InteractionResponseType, MessageComponentInteraction};
+
+use crate::{
+    translation::{tr, Locale},
+    Context,
+};
+
+// presses from anyone but the author still have to be answered, otherwise Discord shows them as
+// failed
+pub(super) async fn reply_not_author(
+    ctx: Context<'_>,
+    press: &MessageComponentInteraction,
+) -> Result<(), crate::Error> {
+    let locale = Locale::new(&ctx.data().translations, Some(&press.locale));
+    press
+        .create_interaction_response(ctx.serenity_context(), |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.content(tr!(locale, "not-author")).ephemeral(true))
+        })
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use poise::serenity_prelude::GuildChannel;
+
+use crate::{
+    translation::{tr, Locale},
+    Context,
+};
+
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    default_member_permissions = "MANAGE_GUILD",
+    subcommands("channel")
+)]
+pub async fn config(_ctx: Context<'_>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+// #[description("Set the channel where watch notifications are sent")]
+#[poise::command(slash_command, guild_only)]
+pub async fn channel(
+    ctx: Context<'_>,
+    #[description = "Channel to send notifications to, leave empty to send direct messages"]
+    #[channel_types("Text")]
+    channel: Option<GuildChannel>,
+) -> Result<(), crate::Error> {
+    ctx.defer_ephemeral().await?;
+
+    let guild_id = ctx.guild_id().ok_or("command must be used in a guild")?;
+    ctx.data()
+        .watcher
+        .settings()
+        .set_guild_channel(guild_id, channel.as_ref().map(|x| x.id))
+        .await?;
+
+    let locale = Locale::from_ctx(ctx);
+    match channel {
+        Some(channel) => {
+            ctx.say(tr!(locale, "config-channel-set", channel: channel.to_string()))
+                .await?
+        }
+        None => ctx.say(tr!(locale, "config-channel-unset")).await?,
+    };
+
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use ubs_lib::Semester;
+
+use super::{autocomplete, defaults, sections};
+use crate::{
+    cache::Query,
+    meeting,
+    render::describe,
+    translation::{tr, Locale},
+    Context,
+};
+
+// #[description("Check classes for overlapping times")]
+#[poise::command(slash_command)]
+pub async fn conflicts(
+    ctx: Context<'_>,
+    #[description = "Semester name, e.g. Fall2023, defaults to your preference or the upcoming semester"]
+    #[autocomplete = "autocomplete::semester"]
+    semester: Option<String>,
+    #[description = "Course and section pairs, e.g. \"CSE115 A1, MTH141 B2\", defaults to your watches"]
+    sections: Option<String>,
+) -> Result<(), crate::Error> {
+    ctx.defer_ephemeral().await?;
+
+    let semester = defaults::semester(ctx, semester).await?;
+
+    let locale = Locale::from_ctx(ctx);
+    let queries = match sections {
+        Some(sections) => match sections::parse(&sections, &semester)? {
+            Some(queries) => queries,
+            None => {
+                ctx.say(tr!(locale, "sections-invalid")).await?;
+                return Ok(());
+            }
+        },
+        None => {
+            let semester_id = semester.parse::<Semester>()?.id().to_owned();
+            ctx.data()
+                .watcher
+                .watches(ctx.author().id)
+                .await?
+                .into_iter()
+                .filter(|x| x.semester == semester_id)
+                .collect()
+        }
+    };
+    if queries.is_empty() {
+        ctx.say(tr!(locale, "sections-empty", semester: semester.as_str()))
+            .await?;
+        return Ok(());
+    }
+
+    let (classes, missing) = sections::classes(ctx, queries).await;
+    let conflicts = meeting::conflicts(classes.iter().map(|(_, class)| class));
+
+    let mut content = vec![if conflicts.is_empty() {
+        tr!(locale, "conflicts-none")
+    } else {
+        tr!(
+            locale,
+            "conflicts-found",
+            conflicts: conflicts
+                .iter()
+                .map(|(i, j)| format!(
+                    "- {}",
+                    tr!(
+                        locale,
+                        "conflicts-pair",
+                        first: describe(locale, &classes[*i].0),
+                        second: describe(locale, &classes[*j].0)
+                    )
+                ))
+                .collect::<Vec<_>>()
+                .join("\n")
+        )
+    }];
+    if !missing.is_empty() {
+        content.push(tr!(
+            locale,
+            "conflicts-missing",
+            classes: sections::list(locale, &missing)
+        ));
+    }
+
+    ctx.say(content.join("\n\n")).await?;
+
+    Ok(())
+}
+
+// the user's other watches during the same semester that overlap with the class
+pub(super) async fn watch_conflicts(
+    ctx: Context<'_>,
+    query: &Query,
+) -> Result<Vec<Query>, crate::Error> {
+    let mut queries = ctx
+        .data()
+        .watcher
+        .watches(ctx.author().id)
+        .await?
+        .into_iter()
+        .filter(|x| {
+            x.semester == query.semester && (x.course != query.course || x.section != query.section)
+        })
+        .collect::<Vec<_>>();
+    if queries.is_empty() {
+        return Ok(Vec::new());
+    }
+    queries.insert(0, query.clone());
+
+    let (classes, _) = sections::classes(ctx, queries).await;
+    if classes.first().map_or(true, |(x, _)| {
+        x.course != query.course || x.section != query.section
+    }) {
+        // the class itself couldn't be found, so there's nothing to compare against
+        return Ok(Vec::new());
+    }
+
+    Ok(meeting::conflicts(classes.iter().map(|(_, class)| class))
+        .into_iter()
+        .filter(|(i, _)| *i == 0)
+        .map(|(_, j)| classes[j].0.clone())
+        .collect())
+}
@@ -0,0 +1,229 @@
+use std::time::Duration;
+
+use poise::serenity_prelude::{
+    ButtonStyle, CollectComponentInteraction, CreateComponents, CreateEmbed,
+    InteractionResponseType,
+};
+use ubs_lib::{model::ClassModel, Course, Semester};
+
+use super::{autocomplete, collector, defaults};
+use crate::{
+    cache::{ClassRecord, Query},
+    interactions, render,
+    translation::{tr, Locale},
+    Context,
+};
+
+// keeps each page well within the embed description limit, as well as the select menu limit of 25
+// options
+const ROWS_PER_PAGE: usize = 15;
+// https://discord.com/developers/docs/interactions/message-components#select-menu-object-select-menu-structure
+const MAX_OPTIONS: usize = 25;
+const TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+// #[description("List every section of a course")]
+#[poise::command(slash_command)]
+pub async fn course(
+    ctx: Context<'_>,
+    #[description = "Course name, e.g. CSE115"]
+    #[autocomplete = "autocomplete::course"]
+    course: String,
+    #[description = "Semester name, e.g. Fall2023, defaults to your preference or the upcoming semester"]
+    #[autocomplete = "autocomplete::semester"]
+    semester: Option<String>,
+) -> Result<(), crate::Error> {
+    ctx.defer().await?;
+
+    let semester = defaults::semester(ctx, semester).await?;
+
+    let locale = Locale::from_ctx(ctx);
+    let schedule = ctx
+        .data()
+        .catalog
+        .schedule(course.parse::<Course>()?, semester.parse::<Semester>()?)
+        .await?;
+    let pages = paginate(&schedule.groups);
+    if pages.is_empty() {
+        ctx.say(tr!(
+            locale,
+            "course-not-found",
+            course: course.as_str(),
+            semester: semester.as_str()
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let title = format!("{} - {}", course, semester);
+    let prefix = ctx.id().to_string();
+    let mut page = 0;
+    let reply = ctx
+        .send(|f| {
+            f.embed(|e| course_embed(e, locale, &title, &pages[page], page, pages.len()))
+                .components(|c| {
+                    course_components(c, locale, &prefix, &pages[page], page, pages.len(), false)
+                })
+        })
+        .await?;
+
+    let filter_prefix = prefix.clone();
+    while let Some(press) = CollectComponentInteraction::new(ctx.serenity_context())
+        .filter(move |press| press.data.custom_id.starts_with(&filter_prefix))
+        .timeout(TIMEOUT)
+        .await
+    {
+        match &press.data.custom_id[prefix.len()..] {
+            "section" => {
+                // anyone can drill into a section, the reply is only visible to them
+                let class = press.data.values.first().and_then(|section| {
+                    pages[page]
+                        .iter()
+                        .find(|x| x.section.as_ref() == Some(section))
+                        .map(|x| (x, section))
+                });
+                if let Some((class, section)) = class {
+                    let query = Query::from_raw(&course, &semester, None, section.to_owned())?;
+                    let record = ClassRecord {
+                        timestamp: schedule.timestamp,
+                        model: (*class).clone(),
+                    };
+                    press
+                        .create_interaction_response(ctx.serenity_context(), |r| {
+                            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                                .interaction_response_data(|d| {
+                                    d.embed(|e| render::class_embed(e, locale, &query, &record))
+                                        .components(|c| {
+                                            c.create_action_row(|r| {
+                                                interactions::class_buttons(r, locale, &query, None)
+                                            })
+                                        })
+                                        .ephemeral(true)
+                                })
+                        })
+                        .await?;
+                }
+                continue;
+            }
+            // only the author can flip through the pages
+            _ if press.user.id != ctx.author().id => {
+                collector::reply_not_author(ctx, &press).await?;
+                continue;
+            }
+            "prev" => page = page.saturating_sub(1),
+            "next" => page = (page + 1).min(pages.len() - 1),
+            _ => continue,
+        }
+
+        press
+            .create_interaction_response(ctx.serenity_context(), |r| {
+                r.kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|d| {
+                        d.embed(|e| {
+                            course_embed(e, locale, &title, &pages[page], page, pages.len())
+                        })
+                        .components(|c| {
+                            course_components(
+                                c,
+                                locale,
+                                &prefix,
+                                &pages[page],
+                                page,
+                                pages.len(),
+                                false,
+                            )
+                        })
+                    })
+            })
+            .await?;
+    }
+
+    // nothing answers the menu or buttons anymore
+    reply
+        .edit(ctx, |f| {
+            f.embed(|e| course_embed(e, locale, &title, &pages[page], page, pages.len()))
+                .components(|c| {
+                    course_components(c, locale, &prefix, &pages[page], page, pages.len(), true)
+                })
+        })
+        .await?;
+
+    Ok(())
+}
+
+// groups are only split across pages when they don't fit on one, so a lecture stays with its
+// recitations wherever possible
+fn paginate(groups: &[Vec<ClassModel>]) -> Vec<Vec<&ClassModel>> {
+    let mut pages: Vec<Vec<&ClassModel>> = Vec::new();
+    for chunk in groups.iter().flat_map(|x| x.chunks(ROWS_PER_PAGE)) {
+        match pages.last_mut() {
+            Some(page) if page.len() + chunk.len() <= ROWS_PER_PAGE => page.extend(chunk),
+            _ => pages.push(chunk.iter().collect()),
+        }
+    }
+    pages
+}
+
+fn course_embed<'a>(
+    e: &'a mut CreateEmbed,
+    locale: Locale,
+    title: &str,
+    classes: &[&ClassModel],
+    page: usize,
+    pages: usize,
+) -> &'a mut CreateEmbed {
+    e.title(title)
+        .description(render::class_table(classes.iter().copied()))
+        .footer(|f| f.text(tr!(locale, "page", page: page + 1, pages: pages)))
+}
+
+fn course_components<'a>(
+    c: &'a mut CreateComponents,
+    locale: Locale,
+    prefix: &str,
+    classes: &[&ClassModel],
+    page: usize,
+    pages: usize,
+    expired: bool,
+) -> &'a mut CreateComponents {
+    let sections = classes
+        .iter()
+        .filter_map(|class| Some((class, class.section.as_ref()?)))
+        .take(MAX_OPTIONS)
+        .collect::<Vec<_>>();
+    // Discord rejects a menu without any options, e.g. when no class on the page has a section
+    if !sections.is_empty() {
+        c.create_action_row(|r| {
+            r.create_select_menu(|m| {
+                m.custom_id(format!("{prefix}section"))
+                    .placeholder(tr!(locale, "course-select-section"))
+                    .disabled(expired)
+                    .options(|o| {
+                        for (class, section) in &sections {
+                            o.create_option(|x| {
+                                x.label(match class.class_type {
+                                    Some(class_type) => format!("{section} — {class_type}"),
+                                    None => section.to_string(),
+                                })
+                                .value(section)
+                            });
+                        }
+                        o
+                    })
+            })
+        });
+    }
+    c.create_action_row(|r| {
+        r.create_button(|b| {
+            b.custom_id(format!("{prefix}prev"))
+                .label(tr!(locale, "previous"))
+                .style(ButtonStyle::Secondary)
+                .disabled(expired || page == 0)
+        })
+        .create_button(|b| {
+            b.custom_id(format!("{prefix}next"))
+                .label(tr!(locale, "next"))
+                .style(ButtonStyle::Secondary)
+                .disabled(expired || page + 1 >= pages)
+        })
+    })
+}
@@ -0,0 +1,32 @@
+use crate::{
+    academic,
+    translation::{tr, Locale},
+    Context,
+};
+
+// the semester given, otherwise the user's default while it's still open, otherwise the one students
+// are most likely choosing classes for
+pub(super) async fn semester(
+    ctx: Context<'_>,
+    semester: Option<String>,
+) -> Result<String, crate::Error> {
+    if let Some(semester) = semester {
+        return Ok(semester);
+    }
+
+    let calendar = &ctx.data().calendar;
+    let today = academic::today();
+    let preferred = ctx
+        .data()
+        .watcher
+        .settings()
+        .semester(ctx.author().id)
+        .await?;
+    match preferred.filter(|x| calendar.semesters(today).contains(x)) {
+        Some(preferred) => Ok(preferred),
+        // the error is what the user is shown
+        None => Ok(calendar
+            .default_semester(today)
+            .ok_or_else(|| tr!(Locale::from_ctx(ctx), "semester-unknown"))?),
+    }
+}
@@ -1,4 +1,24 @@
+mod admin;
+mod autocomplete;
+mod calendar;
 mod class;
+mod collector;
+mod config;
+mod conflicts;
+mod course;
+mod defaults;
 mod general;
+mod plan;
+mod preferences;
+mod search;
+mod sections;
 
-pub use class::{info, rawinfo, unwatch, watch};
+pub use admin::admin;
+pub use calendar::calendar;
+pub use class::{info, rawinfo, snooze, unwatch, watch};
+pub use config::config;
+pub use conflicts::conflicts;
+pub use course::course;
+pub use plan::plan;
+pub use preferences::preferences;
+pub use search::search;
@@ -0,0 +1,297 @@
+use std::time::Duration;
+
+use poise::serenity_prelude::{
+    ButtonStyle, CollectComponentInteraction, CreateComponents, CreateEmbed,
+    InteractionResponseType, MessageComponentInteraction,
+};
+use ubs_lib::{Course, Semester};
+
+use super::{autocomplete, collector, defaults, sections};
+use crate::{
+    cache::Query,
+    notifier::Recipient,
+    planner::{self, Plan, Preferences},
+    render,
+    translation::{tr, Locale},
+    Context,
+};
+
+// every combination is checked against each other, so the number of courses is kept small
+const MAX_COURSES: usize = 6;
+const MAX_PLANS: usize = 5;
+const TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+// #[description("Build conflict-free schedules from a list of courses")]
+#[poise::command(slash_command)]
+pub async fn plan(
+    ctx: Context<'_>,
+    #[description = "Course names, e.g. \"CSE115, MTH141\""] courses: String,
+    #[description = "Semester name, e.g. Fall2023, defaults to your preference or the upcoming semester"]
+    #[autocomplete = "autocomplete::semester"]
+    semester: Option<String>,
+    #[description = "Prefer sections with open seats, defaults to true"] open_seats: Option<bool>,
+    #[description = "Prefer no classes before 10 AM, defaults to true"] late_start: Option<bool>,
+    #[description = "Prefer classes on fewer days with less time between them, defaults to true"]
+    compact: Option<bool>,
+) -> Result<(), crate::Error> {
+    ctx.defer().await?;
+
+    let semester = defaults::semester(ctx, semester).await?;
+
+    let locale = Locale::from_ctx(ctx);
+    let mut unique = Vec::new();
+    for course in courses
+        .split(|x: char| x == ',' || x.is_whitespace())
+        .filter(|x| !x.is_empty())
+    {
+        let course = course.to_uppercase();
+        if !unique.contains(&course) {
+            unique.push(course);
+        }
+    }
+    let courses = unique;
+    if courses.is_empty() || courses.len() > MAX_COURSES {
+        ctx.say(tr!(locale, "plan-invalid", max: MAX_COURSES))
+            .await?;
+        return Ok(());
+    }
+
+    let mut schedules = Vec::new();
+    for course in &courses {
+        let schedule = ctx
+            .data()
+            .catalog
+            .schedule(course.parse::<Course>()?, semester.parse::<Semester>()?)
+            .await?;
+        if schedule.groups.is_empty() {
+            ctx.say(tr!(
+                locale,
+                "course-not-found",
+                course: course.as_str(),
+                semester: semester.as_str()
+            ))
+            .await?;
+            return Ok(());
+        }
+        schedules.push(schedule);
+    }
+
+    let preferences = Preferences {
+        open_seats: open_seats.unwrap_or(true),
+        late_start: late_start.unwrap_or(true),
+        compact: compact.unwrap_or(true),
+    };
+    // enumerating can take a while, so it's kept off of the async runtime
+    let plans = tokio::task::spawn_blocking(move || {
+        planner::plans(
+            &schedules
+                .iter()
+                .map(|x| x.groups.as_slice())
+                .collect::<Vec<_>>(),
+            preferences,
+            MAX_PLANS,
+        )
+    })
+    .await?;
+    if plans.is_empty() {
+        ctx.say(tr!(locale, "plan-none")).await?;
+        return Ok(());
+    }
+
+    let prefix = ctx.id().to_string();
+    let mut page = 0;
+    let reply = ctx
+        .send(|f| {
+            f.embed(|e| {
+                plan_embed(
+                    e,
+                    locale,
+                    &semester,
+                    &courses,
+                    &plans[page],
+                    page,
+                    plans.len(),
+                )
+            })
+            .components(|c| plan_components(c, locale, &prefix, page, plans.len(), false))
+        })
+        .await?;
+
+    let filter_prefix = prefix.clone();
+    while let Some(press) = CollectComponentInteraction::new(ctx.serenity_context())
+        .filter(move |press| press.data.custom_id.starts_with(&filter_prefix))
+        .timeout(TIMEOUT)
+        .await
+    {
+        match &press.data.custom_id[prefix.len()..] {
+            "watch" => {
+                // anyone can watch the schedule that's shown, not just the author
+                let queries = plans[page]
+                    .classes
+                    .iter()
+                    .filter_map(|(i, class)| {
+                        Query::from_raw(&courses[*i], &semester, None, class.section.clone()?).ok()
+                    })
+                    .collect::<Vec<_>>();
+                let locale = Locale::new(&ctx.data().translations, Some(&press.locale));
+                let content = if ctx.data().blocklist.is_blocked(press.user.id).await? {
+                    tr!(locale, "blocked")
+                } else {
+                    watch_all(ctx, &press, locale, &queries).await?
+                };
+                press
+                    .create_interaction_response(ctx.serenity_context(), |r| {
+                        r.kind(InteractionResponseType::ChannelMessageWithSource)
+                            .interaction_response_data(|d| d.content(content).ephemeral(true))
+                    })
+                    .await?;
+                continue;
+            }
+            // only the author can flip through the schedules
+            _ if press.user.id != ctx.author().id => {
+                collector::reply_not_author(ctx, &press).await?;
+                continue;
+            }
+            "prev" => page = page.saturating_sub(1),
+            "next" => page = (page + 1).min(plans.len() - 1),
+            _ => continue,
+        }
+
+        press
+            .create_interaction_response(ctx.serenity_context(), |r| {
+                r.kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|d| {
+                        d.embed(|e| {
+                            plan_embed(
+                                e,
+                                locale,
+                                &semester,
+                                &courses,
+                                &plans[page],
+                                page,
+                                plans.len(),
+                            )
+                        })
+                        .components(|c| {
+                            plan_components(c, locale, &prefix, page, plans.len(), false)
+                        })
+                    })
+            })
+            .await?;
+    }
+
+    // nothing answers the buttons anymore
+    reply
+        .edit(ctx, |f| {
+            f.embed(|e| {
+                plan_embed(
+                    e,
+                    locale,
+                    &semester,
+                    &courses,
+                    &plans[page],
+                    page,
+                    plans.len(),
+                )
+            })
+            .components(|c| plan_components(c, locale, &prefix, page, plans.len(), true))
+        })
+        .await?;
+
+    Ok(())
+}
+
+// the classes before a limit is reached stay watched
+async fn watch_all(
+    ctx: Context<'_>,
+    press: &MessageComponentInteraction,
+    locale: Locale<'_>,
+    queries: &[Query],
+) -> Result<String, crate::Error> {
+    for query in queries {
+        let added = ctx
+            .data()
+            .watcher
+            .add_watch(
+                Recipient {
+                    user_id: press.user.id,
+                    guild_id: press.guild_id,
+                },
+                query,
+                None,
+            )
+            .await;
+        if let Err(err) = added {
+            return Ok(render::watch_error(locale, &err).ok_or(err)?);
+        }
+    }
+    ctx.data()
+        .watcher
+        .settings()
+        .set_locale(press.user.id, &press.locale)
+        .await?;
+
+    Ok(tr!(
+        locale,
+        "plan-watched",
+        classes: sections::list(locale, queries)
+    ))
+}
+
+// a table for each course, in the order they were given
+fn plan_embed<'a>(
+    e: &'a mut CreateEmbed,
+    locale: Locale,
+    semester: &str,
+    courses: &[String],
+    plan: &Plan,
+    page: usize,
+    pages: usize,
+) -> &'a mut CreateEmbed {
+    let mut description = vec![tr!(locale, "plan-days", count: plan.days)];
+    for (i, course) in courses.iter().enumerate() {
+        description.push(format!(
+            "**{course}**\n{}",
+            render::class_table(
+                plan.classes
+                    .iter()
+                    .filter(|(j, _)| i == *j)
+                    .map(|(_, class)| class)
+            )
+        ));
+    }
+
+    e.title(tr!(locale, "plan-title", option: page + 1, semester: semester))
+        .description(description.join("\n"))
+        .footer(|f| f.text(tr!(locale, "page", page: page + 1, pages: pages)))
+}
+
+fn plan_components<'a>(
+    c: &'a mut CreateComponents,
+    locale: Locale,
+    prefix: &str,
+    page: usize,
+    pages: usize,
+    expired: bool,
+) -> &'a mut CreateComponents {
+    c.create_action_row(|r| {
+        r.create_button(|b| {
+            b.custom_id(format!("{prefix}watch"))
+                .label(tr!(locale, "plan-watch"))
+                .style(ButtonStyle::Primary)
+                .disabled(expired)
+        })
+        .create_button(|b| {
+            b.custom_id(format!("{prefix}prev"))
+                .label(tr!(locale, "previous"))
+                .style(ButtonStyle::Secondary)
+                .disabled(expired || page == 0)
+        })
+        .create_button(|b| {
+            b.custom_id(format!("{prefix}next"))
+                .label(tr!(locale, "next"))
+                .style(ButtonStyle::Secondary)
+                .disabled(expired || page + 1 >= pages)
+        })
+    })
+}
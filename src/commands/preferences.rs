@@ -0,0 +1,218 @@
+use std::str::FromStr;
+
+use chrono_tz::Tz;
+use sqlx::types::chrono::NaiveTime;
+use ubs_lib::Semester;
+
+use super::autocomplete;
+use crate::{
+    catalog,
+    settings::Delivery,
+    translation::{tr, Locale},
+    Context,
+};
+
+const TIME_FORMAT: &str = "%H:%M";
+
+#[poise::command(
+    slash_command,
+    subcommands("delivery", "timezone", "quiet", "digest", "semester")
+)]
+pub async fn preferences(_ctx: Context<'_>) -> Result<(), crate::Error> {
+    Ok(())
+}
+
+// #[description("Set how watch notifications are delivered to you")]
+#[poise::command(slash_command)]
+pub async fn delivery(
+    ctx: Context<'_>,
+    #[description = "Where to send your watch notifications"] delivery: Delivery,
+) -> Result<(), crate::Error> {
+    ctx.defer_ephemeral().await?;
+
+    ctx.data()
+        .watcher
+        .settings()
+        .set_delivery(ctx.author().id, delivery)
+        .await?;
+
+    ctx.say(tr!(
+        Locale::from_ctx(ctx),
+        match delivery {
+            Delivery::Dm => "delivery-dm",
+            Delivery::Channel => "delivery-channel",
+            Delivery::Both => "delivery-both",
+        }
+    ))
+    .await?;
+
+    Ok(())
+}
+
+// #[description("Set the timezone used for your quiet hours and digest")]
+#[poise::command(slash_command)]
+pub async fn timezone(
+    ctx: Context<'_>,
+    #[description = "IANA timezone, e.g. America/New_York"] timezone: String,
+) -> Result<(), crate::Error> {
+    ctx.defer_ephemeral().await?;
+
+    let locale = Locale::from_ctx(ctx);
+    let timezone = match timezone.parse::<Tz>() {
+        Ok(timezone) => timezone,
+        Err(_) => {
+            ctx.say(tr!(locale, "timezone-invalid", timezone: timezone))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    ctx.data()
+        .watcher
+        .settings()
+        .set_timezone(ctx.author().id, timezone)
+        .await?;
+
+    ctx.say(tr!(locale, "timezone-set", timezone: timezone.name()))
+        .await?;
+
+    Ok(())
+}
+
+// #[description("Hold watch notifications during a period of the day")]
+#[poise::command(slash_command)]
+pub async fn quiet(
+    ctx: Context<'_>,
+    #[description = "Start of quiet hours as HH:MM, leave both empty to disable"] start: Option<
+        String,
+    >,
+    #[description = "End of quiet hours as HH:MM, leave both empty to disable"] end: Option<String>,
+) -> Result<(), crate::Error> {
+    ctx.defer_ephemeral().await?;
+
+    let locale = Locale::from_ctx(ctx);
+    let quiet_hours = match (start, end) {
+        (Some(start), Some(end)) => match (parse_time(&start), parse_time(&end)) {
+            (Some(start), Some(end)) => Some((start, end)),
+            _ => {
+                ctx.say(tr!(locale, "time-invalid", example: "22:30"))
+                    .await?;
+                return Ok(());
+            }
+        },
+        (None, None) => None,
+        // a single bound is most likely a mistake, rather than a request to disable
+        _ => {
+            ctx.say(tr!(locale, "quiet-incomplete")).await?;
+            return Ok(());
+        }
+    };
+
+    ctx.data()
+        .watcher
+        .settings()
+        .set_quiet_hours(ctx.author().id, quiet_hours)
+        .await?;
+
+    match quiet_hours {
+        Some((start, end)) => {
+            ctx.say(tr!(
+                locale,
+                "quiet-set",
+                start: start.format(TIME_FORMAT).to_string(),
+                end: end.format(TIME_FORMAT).to_string()
+            ))
+            .await?
+        }
+        None => ctx.say(tr!(locale, "quiet-disabled")).await?,
+    };
+
+    Ok(())
+}
+
+// #[description("Receive a single summary of watch notifications each day")]
+#[poise::command(slash_command)]
+pub async fn digest(
+    ctx: Context<'_>,
+    #[description = "Time to send the summary as HH:MM, leave empty to disable"] time: Option<
+        String,
+    >,
+) -> Result<(), crate::Error> {
+    ctx.defer_ephemeral().await?;
+
+    let locale = Locale::from_ctx(ctx);
+    let digest_time = match time.as_deref().map(parse_time) {
+        Some(Some(time)) => Some(time),
+        Some(None) => {
+            ctx.say(tr!(locale, "time-invalid", example: "08:00"))
+                .await?;
+            return Ok(());
+        }
+        None => None,
+    };
+
+    ctx.data()
+        .watcher
+        .settings()
+        .set_digest_time(ctx.author().id, digest_time)
+        .await?;
+
+    match digest_time {
+        Some(time) => {
+            ctx.say(tr!(
+                locale,
+                "digest-set",
+                time: time.format(TIME_FORMAT).to_string()
+            ))
+            .await?
+        }
+        None => ctx.say(tr!(locale, "digest-disabled")).await?,
+    };
+
+    Ok(())
+}
+
+// #[description("Set the semester commands use when none is given")]
+#[poise::command(slash_command)]
+pub async fn semester(
+    ctx: Context<'_>,
+    #[description = "Semester name, e.g. Fall2023, leave empty to use the upcoming semester"]
+    #[autocomplete = "autocomplete::semester"]
+    semester: Option<String>,
+) -> Result<(), crate::Error> {
+    ctx.defer_ephemeral().await?;
+
+    let locale = Locale::from_ctx(ctx);
+    // stored by name so that it's shown the same way it's autocompleted
+    let name = match semester {
+        Some(semester) => match Semester::from_str(&semester)
+            .ok()
+            .and_then(|x| catalog::semester_name(x.id()))
+        {
+            Some(name) => Some(name),
+            None => {
+                ctx.say(tr!(locale, "semester-invalid", semester: semester))
+                    .await?;
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    ctx.data()
+        .watcher
+        .settings()
+        .set_semester(ctx.author().id, name.as_deref())
+        .await?;
+
+    match name {
+        Some(name) => ctx.say(tr!(locale, "semester-set", semester: name)).await?,
+        None => ctx.say(tr!(locale, "semester-cleared")).await?,
+    };
+
+    Ok(())
+}
+
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time.trim(), TIME_FORMAT).ok()
+}
@@ -0,0 +1,340 @@
+use std::time::Duration;
+
+use poise::serenity_prelude::{
+    ButtonStyle, CollectComponentInteraction, CreateComponents, CreateEmbed,
+    InteractionResponseType, MessageComponentInteraction,
+};
+use ubs_lib::{Course, Semester};
+
+use super::{autocomplete, collector, defaults};
+use crate::{
+    cache::{ClassRecord, Query, DEFAULT_CAREER},
+    catalog::SearchResult,
+    interactions,
+    notifier::Recipient,
+    render::{self, describe},
+    translation::{tr, Locale},
+    Context,
+};
+
+const PAGE_SIZE: usize = 4;
+// https://discord.com/developers/docs/interactions/message-components#select-menu-object-select-menu-structure
+const MAX_OPTIONS: usize = 25;
+const TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+// #[description("Search for a course by subject and number")]
+#[poise::command(slash_command)]
+pub async fn search(
+    ctx: Context<'_>,
+    #[description = "Course name, e.g. \"CSE 25\", titles such as \"data structures\" aren't searched"]
+    query: String,
+    #[description = "Semester name, e.g. Fall2023, defaults to your preference or the upcoming semester"]
+    #[autocomplete = "autocomplete::semester"]
+    semester: Option<String>,
+) -> Result<(), crate::Error> {
+    ctx.defer().await?;
+
+    let semester = defaults::semester(ctx, semester).await?;
+
+    let locale = Locale::from_ctx(ctx);
+    let seen_ids = ctx.data().watcher.cache().courses().await?;
+    let results = ctx.data().catalog.search(&query, &seen_ids);
+    if results.is_empty() {
+        ctx.say(format!(
+            "{}\n\n*{}*",
+            tr!(locale, "search-not-found", query: query.as_str()),
+            tr!(locale, "search-missing-mapping")
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let pages = results.chunks(PAGE_SIZE).collect::<Vec<_>>();
+    let prefix = ctx.id().to_string();
+    let mut page = 0;
+    let reply = ctx
+        .send(|f| {
+            f.embed(|e| search_embed(e, locale, &query, pages[page], page, pages.len()))
+                .components(|c| {
+                    search_components(c, locale, &prefix, pages[page], page, pages.len(), false)
+                })
+        })
+        .await?;
+
+    let filter_prefix = prefix.clone();
+    while let Some(press) = CollectComponentInteraction::new(ctx.serenity_context())
+        .filter(move |press| press.data.custom_id.starts_with(&filter_prefix))
+        .timeout(TIMEOUT)
+        .await
+    {
+        if press.user.id != ctx.author().id {
+            collector::reply_not_author(ctx, &press).await?;
+            continue;
+        }
+
+        let action = &press.data.custom_id[prefix.len()..];
+        // buttons and menus refer to results by their index across every page
+        let (action, i, result) = match action.split_once(':') {
+            Some((action @ ("info" | "watch" | "info-section" | "watch-section"), i)) => {
+                match i.parse::<usize>().ok().and_then(|i| results.get(i)) {
+                    Some(result) => (action, i, result),
+                    None => continue,
+                }
+            }
+            _ => {
+                match action {
+                    "prev" => page = page.saturating_sub(1),
+                    "next" => page = (page + 1).min(pages.len() - 1),
+                    _ => continue,
+                }
+
+                press
+                    .create_interaction_response(ctx.serenity_context(), |r| {
+                        r.kind(InteractionResponseType::UpdateMessage)
+                            .interaction_response_data(|d| {
+                                d.embed(|e| {
+                                    search_embed(e, locale, &query, pages[page], page, pages.len())
+                                })
+                                .components(|c| {
+                                    search_components(
+                                        c,
+                                        locale,
+                                        &prefix,
+                                        pages[page],
+                                        page,
+                                        pages.len(),
+                                        false,
+                                    )
+                                })
+                            })
+                    })
+                    .await?;
+                continue;
+            }
+        };
+
+        // the schedule may have to be fetched, which can take longer than Discord waits
+        press
+            .create_interaction_response(ctx.serenity_context(), |r| {
+                r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                    .interaction_response_data(|d| d.ephemeral(true))
+            })
+            .await?;
+        let schedule = ctx
+            .data()
+            .catalog
+            .schedule(course(result)?, semester.parse::<Semester>()?)
+            .await?;
+        // a menu without any options is rejected by Discord
+        if schedule.sections().next().is_none() {
+            let content = tr!(
+                locale,
+                "course-not-found",
+                course: result.course.as_str(),
+                semester: semester.as_str()
+            );
+            press
+                .edit_original_interaction_response(ctx.serenity_context(), |r| r.content(content))
+                .await?;
+            continue;
+        }
+
+        match action {
+            // a section has to be picked before anything can be done with the course
+            "info" | "watch" => {
+                let sections = schedule.sections().collect::<Vec<_>>();
+                let mut content =
+                    tr!(locale, "search-select-section", course: result.course.as_str());
+                if sections.len() > MAX_OPTIONS {
+                    content.push('\n');
+                    content.push_str(&tr!(
+                        locale,
+                        "search-more-sections",
+                        max: MAX_OPTIONS,
+                        command: ctx.data().command_mention("course")
+                    ));
+                }
+                press
+                    .edit_original_interaction_response(ctx.serenity_context(), |r| {
+                        r.content(content).components(|c| {
+                            c.create_action_row(|r| {
+                                r.create_select_menu(|m| {
+                                    m.custom_id(format!("{prefix}{action}-section:{i}"))
+                                        .placeholder(tr!(locale, "course-select-section"))
+                                        .options(|o| {
+                                            for section in sections.iter().take(MAX_OPTIONS) {
+                                                o.create_option(|x| {
+                                                    x.label(section).value(section)
+                                                });
+                                            }
+                                            o
+                                        })
+                                })
+                            })
+                        })
+                    })
+                    .await?;
+            }
+            _ => {
+                let class = press
+                    .data
+                    .values
+                    .first()
+                    .and_then(|section| schedule.class(section));
+                let (class, section) = match class.and_then(|x| x.section.clone().map(|y| (x, y))) {
+                    Some(class) => class,
+                    None => continue,
+                };
+                let query = if result.known {
+                    Query::from_raw(&result.course, &semester, None, section)?
+                } else {
+                    Query::from_ids(
+                        result.course.clone(),
+                        semester.parse::<Semester>()?.id().to_owned(),
+                        DEFAULT_CAREER.to_owned(),
+                        section,
+                    )
+                };
+
+                if action == "info-section" {
+                    let record = ClassRecord {
+                        timestamp: schedule.timestamp,
+                        model: class.clone(),
+                    };
+                    press
+                        .edit_original_interaction_response(ctx.serenity_context(), |r| {
+                            r.embed(|e| render::class_embed(e, locale, &query, &record))
+                                .components(|c| {
+                                    c.create_action_row(|r| {
+                                        interactions::class_buttons(r, locale, &query, None)
+                                    })
+                                })
+                        })
+                        .await?;
+                    continue;
+                }
+
+                let content = watch(ctx, &press, locale, &query).await?;
+                press
+                    .edit_original_interaction_response(ctx.serenity_context(), |r| {
+                        r.content(content)
+                    })
+                    .await?;
+            }
+        }
+    }
+
+    // nothing answers the buttons anymore
+    reply
+        .edit(ctx, |f| {
+            f.embed(|e| search_embed(e, locale, &query, pages[page], page, pages.len()))
+                .components(|c| {
+                    search_components(c, locale, &prefix, pages[page], page, pages.len(), true)
+                })
+        })
+        .await?;
+
+    Ok(())
+}
+
+// known courses are looked up by name, anything else by its raw id
+fn course(result: &SearchResult) -> Result<Course, crate::Error> {
+    Ok(if result.known {
+        result.course.parse::<Course>()?
+    } else {
+        Course::Raw(result.course.clone())
+    })
+}
+
+async fn watch(
+    ctx: Context<'_>,
+    press: &MessageComponentInteraction,
+    locale: Locale<'_>,
+    query: &Query,
+) -> Result<String, crate::Error> {
+    let added = ctx
+        .data()
+        .watcher
+        .add_watch(
+            Recipient {
+                user_id: press.user.id,
+                guild_id: press.guild_id,
+            },
+            query,
+            None,
+        )
+        .await;
+    if let Err(err) = added {
+        return Ok(render::watch_error(locale, &err).ok_or(err)?);
+    }
+    ctx.data()
+        .watcher
+        .settings()
+        .set_locale(press.user.id, &press.locale)
+        .await?;
+
+    Ok(tr!(locale, "watch-added", class: describe(locale, query)))
+}
+
+fn search_embed<'a>(
+    e: &'a mut CreateEmbed,
+    locale: Locale,
+    query: &str,
+    results: &[SearchResult],
+    page: usize,
+    pages: usize,
+) -> &'a mut CreateEmbed {
+    e.title(tr!(locale, "search-title", query: query))
+        .description(
+            results
+                .iter()
+                .map(|x| format!("**{}**", x.course))
+                .collect::<Vec<String>>()
+                .join("\n"),
+        )
+        .footer(|f| f.text(tr!(locale, "page", page: page + 1, pages: pages)))
+}
+
+fn search_components<'a>(
+    c: &'a mut CreateComponents,
+    locale: Locale,
+    prefix: &str,
+    results: &[SearchResult],
+    page: usize,
+    pages: usize,
+    expired: bool,
+) -> &'a mut CreateComponents {
+    for (i, result) in results.iter().enumerate() {
+        let i = page * PAGE_SIZE + i;
+        c.create_action_row(|r| {
+            r.create_button(|b| {
+                b.custom_id(format!("{prefix}info:{i}"))
+                    .label(tr!(locale, "search-info", course: result.course.as_str()))
+                    .style(ButtonStyle::Primary)
+                    .disabled(expired)
+            });
+            // watches are stored by id, so raw ids are watched the same way as from `/rawinfo`
+            r.create_button(|b| {
+                b.custom_id(format!("{prefix}watch:{i}"))
+                    .label(tr!(locale, "search-watch", course: result.course.as_str()))
+                    .style(ButtonStyle::Secondary)
+                    .disabled(expired)
+            })
+        });
+    }
+
+    c.create_action_row(|r| {
+        r.create_button(|b| {
+            b.custom_id(format!("{prefix}prev"))
+                .label(tr!(locale, "previous"))
+                .style(ButtonStyle::Secondary)
+                .disabled(expired || page == 0)
+        })
+        .create_button(|b| {
+            b.custom_id(format!("{prefix}next"))
+                .label(tr!(locale, "next"))
+                .style(ButtonStyle::Secondary)
+                .disabled(expired || page + 1 >= pages)
+        })
+    })
+}
@@ -0,0 +1,76 @@
+use tracing::error;
+use ubs_lib::{model::ClassModel, Course, Semester};
+
+use crate::{
+    cache::{FetchClassError, Query},
+    render::describe,
+    translation::Locale,
+    Context,
+};
+
+// sections listed as course and section pairs, e.g. "CSE115 A1, MTH141 B2"
+//
+// returns `None` if a pair is missing its section
+pub(super) fn parse(sections: &str, semester: &str) -> Result<Option<Vec<Query>>, crate::Error> {
+    let mut queries = Vec::new();
+    for pair in sections.split(',').filter(|x| !x.trim().is_empty()) {
+        let (course, section) = match pair.split_whitespace().collect::<Vec<_>>()[..] {
+            [course, section] => (course, section),
+            _ => return Ok(None),
+        };
+        queries.push(Query::from_raw(course, semester, None, section.to_owned())?);
+    }
+
+    Ok(Some(queries))
+}
+
+// splits the queries into the classes that were found and the ones that weren't
+pub(super) async fn classes(
+    ctx: Context<'_>,
+    queries: Vec<Query>,
+) -> (Vec<(Query, ClassModel)>, Vec<Query>) {
+    let mut found = Vec::new();
+    let mut missing = Vec::new();
+    for query in queries {
+        match class(ctx, &query).await {
+            Some(class) => found.push((query, class)),
+            None => missing.push(query),
+        }
+    }
+
+    (found, missing)
+}
+
+// watched classes are already cached, anything else is taken from its schedule
+async fn class(ctx: Context<'_>, query: &Query) -> Option<ClassModel> {
+    match ctx.data().watcher.cache().get(query).await {
+        Ok(record) => return Some(record.model),
+        Err(FetchClassError::Sql(sqlx::Error::RowNotFound)) => {}
+        Err(err) => error!("failed to get {:?} from cache: {:?}", query, err),
+    }
+
+    match ctx
+        .data()
+        .catalog
+        .schedule(
+            Course::Raw(query.course.clone()),
+            Semester::Raw(query.semester.clone()),
+        )
+        .await
+    {
+        Ok(schedule) => schedule.class(&query.section).cloned(),
+        Err(err) => {
+            error!("failed to get schedule for {:?}: {:?}", query, err);
+            None
+        }
+    }
+}
+
+// one class per line, as a markdown list
+pub(super) fn list(locale: Locale, queries: &[Query]) -> String {
+    queries
+        .iter()
+        .map(|x| format!("- {}", describe(locale, x)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
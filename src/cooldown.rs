@@ -0,0 +1,58 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use poise::serenity_prelude::UserId;
+
+// per-user cooldown for what isn't a command, e.g. buttons that fetch from UB, commands use poise's
+#[derive(Debug)]
+pub struct Cooldown {
+    duration: Duration,
+    started: Mutex<HashMap<UserId, Instant>>,
+}
+
+impl Cooldown {
+    pub fn new(duration: Duration) -> Cooldown {
+        Cooldown {
+            duration,
+            started: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // starts the cooldown for the user, or returns how much of it is left if it's running
+    pub fn hit(&self, user_id: UserId) -> Option<Duration> {
+        let now = Instant::now();
+        let mut started = self.started.lock().unwrap();
+        // finished cooldowns are dropped so that the map doesn't grow with every user
+        started.retain(|_, x| now.duration_since(*x) < self.duration);
+        match started.get(&user_id) {
+            Some(x) => Some(self.duration - now.duration_since(*x)),
+            None => {
+                started.insert(user_id, now);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cooldown_is_per_user() {
+        let cooldown = Cooldown::new(Duration::from_secs(60));
+        assert_eq!(cooldown.hit(UserId(1)), None);
+        assert!(cooldown.hit(UserId(1)).is_some());
+        assert_eq!(cooldown.hit(UserId(2)), None);
+    }
+
+    #[test]
+    fn zero_disables_the_cooldown() {
+        let cooldown = Cooldown::new(Duration::ZERO);
+        assert_eq!(cooldown.hit(UserId(1)), None);
+        assert_eq!(cooldown.hit(UserId(1)), None);
+    }
+}
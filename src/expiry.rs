@@ -0,0 +1,162 @@
+use chrono_tz::Tz;
+use sqlx::types::chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc, Weekday};
+
+// anything longer is almost certainly a typo, and would overflow a `Duration` soon enough
+const MAX_AMOUNT: i64 = 10_000;
+
+// when a watch ends on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    At(DateTime<Utc>),
+    // removed once a notification about the class is sent
+    FirstNotification,
+}
+
+// e.g. "for 3 days", "until Friday", "until 2023-10-20" or "after first notification", a day lasts
+// until midnight in `timezone`
+pub fn parse_expiry(input: &str, now: DateTime<Utc>, timezone: Tz) -> Option<Expiry> {
+    let input = input.trim().to_lowercase();
+    if let Some(duration) = input.strip_prefix("for ") {
+        return parse_duration(duration).map(|x| Expiry::At(now + x));
+    }
+    if let Some(date) = input.strip_prefix("until ") {
+        let today = now.with_timezone(&timezone).date_naive();
+        let midnight = parse_date(date.trim(), today)?
+            .succ_opt()?
+            .and_hms_opt(0, 0, 0)?;
+        // a date that has already passed would remove the watch before it's ever polled
+        return timezone
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map(|x| x.with_timezone(&Utc))
+            .filter(|x| *x > now)
+            .map(Expiry::At);
+    }
+
+    match input.as_str() {
+        "after first notification" | "after notification" | "once" => {
+            Some(Expiry::FirstNotification)
+        }
+        _ => None,
+    }
+}
+
+// e.g. "3 days", "12h" or "1 week"
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    let input = input.trim().to_lowercase();
+    let (amount, unit) = input.split_at(input.find(|x: char| !x.is_ascii_digit())?);
+    let amount = amount
+        .parse::<i64>()
+        .ok()
+        .filter(|x| (1..=MAX_AMOUNT).contains(x))?;
+    Some(match unit.trim().trim_end_matches('s') {
+        "m" | "min" | "minute" => Duration::minutes(amount),
+        "h" | "hr" | "hour" => Duration::hours(amount),
+        "d" | "day" => Duration::days(amount),
+        "w" | "wk" | "week" => Duration::weeks(amount),
+        _ => return None,
+    })
+}
+
+// the next occurrence of a weekday includes today
+fn parse_date(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match input {
+        "today" => Some(today),
+        "tomorrow" => today.succ_opt(),
+        _ => match input.parse::<Weekday>() {
+            Ok(weekday) => {
+                let days = (7 + weekday.num_days_from_monday()
+                    - today.weekday().num_days_from_monday())
+                    % 7;
+                Some(today + Duration::days(days.into()))
+            }
+            Err(_) => NaiveDate::parse_from_str(input, "%Y-%m-%d").ok(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEZONE: Tz = chrono_tz::America::New_York;
+
+    // a Wednesday afternoon in UB local time
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, 10, 18, 18, 0, 0).unwrap()
+    }
+
+    fn midnight(year: i32, month: u32, day: u32) -> Expiry {
+        Expiry::At(
+            TIMEZONE
+                .with_ymd_and_hms(year, month, day, 0, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc),
+        )
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("3 days"), Some(Duration::days(3)));
+        assert_eq!(parse_duration("12h"), Some(Duration::hours(12)));
+        assert_eq!(parse_duration("1 Week"), Some(Duration::weeks(1)));
+        assert_eq!(parse_duration("90 minutes"), Some(Duration::minutes(90)));
+    }
+
+    #[test]
+    fn invalid_durations() {
+        assert_eq!(parse_duration("days"), None);
+        assert_eq!(parse_duration("0 days"), None);
+        assert_eq!(parse_duration("3 fortnights"), None);
+        assert_eq!(parse_duration("-3 days"), None);
+        assert_eq!(parse_duration("100000 days"), None);
+    }
+
+    #[test]
+    fn expiry_for_a_duration() {
+        assert_eq!(
+            parse_expiry("for 3 days", now(), TIMEZONE),
+            Some(Expiry::At(now() + Duration::days(3)))
+        );
+    }
+
+    #[test]
+    fn expiry_until_a_day() {
+        // the day lasts until its midnight
+        assert_eq!(
+            parse_expiry("until today", now(), TIMEZONE),
+            Some(midnight(2023, 10, 19))
+        );
+        assert_eq!(
+            parse_expiry("Until Friday", now(), TIMEZONE),
+            Some(midnight(2023, 10, 21))
+        );
+        assert_eq!(
+            parse_expiry("until wednesday", now(), TIMEZONE),
+            Some(midnight(2023, 10, 19))
+        );
+        assert_eq!(
+            parse_expiry("until 2023-12-01", now(), TIMEZONE),
+            Some(midnight(2023, 12, 2))
+        );
+    }
+
+    #[test]
+    fn expiry_in_the_past() {
+        assert_eq!(parse_expiry("until 2023-10-17", now(), TIMEZONE), None);
+        assert_eq!(parse_expiry("until 2020-01-01", now(), TIMEZONE), None);
+    }
+
+    #[test]
+    fn expiry_after_notification() {
+        assert_eq!(
+            parse_expiry("after first notification", now(), TIMEZONE),
+            Some(Expiry::FirstNotification)
+        );
+        assert_eq!(
+            parse_expiry(" once ", now(), TIMEZONE),
+            Some(Expiry::FirstNotification)
+        );
+        assert_eq!(parse_expiry("forever", now(), TIMEZONE), None);
+    }
+}
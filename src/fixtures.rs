@@ -0,0 +1,19 @@
+use serde::Deserialize;
+use sqlx::types::JsonValue;
+use ubs_lib::model::ClassModel;
+
+// an open class the way it's stored in the cache, e.g.
+// `class("A1", "Lecture", &["Monday"], "09:00", "09:50")`, anything else is set on the result
+pub fn class(section: &str, class_type: &str, days: &[&str], start: &str, end: &str) -> ClassModel {
+    let value = [
+        ("section", JsonValue::from(section)),
+        ("class_type", JsonValue::from(class_type)),
+        ("days_of_week", JsonValue::from(days.to_vec())),
+        ("start_time", JsonValue::from(format!("{start}:00"))),
+        ("end_time", JsonValue::from(format!("{end}:00"))),
+        ("is_open", JsonValue::from(true)),
+    ]
+    .into_iter()
+    .collect::<JsonValue>();
+    ClassModel::deserialize(value).unwrap()
+}
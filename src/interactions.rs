@@ -0,0 +1,338 @@
+use poise::serenity_prelude::{
+    ButtonStyle, Context, CreateActionRow, InteractionResponseType, MessageComponentInteraction,
+};
+
+use ubs_lib::Course;
+
+use crate::{
+    cache::Query,
+    catalog,
+    notifier::Recipient,
+    render::{self, describe},
+    translation::{tr, Locale},
+    watcher::Check,
+    Data, MAX_AGE,
+};
+
+// prefix of every custom id handled here, anything else belongs to a command's collector
+const PREFIX: &str = "class";
+const HISTORY_LIMIT: i64 = 500;
+const HISTORY_ENTRIES: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Watch,
+    Unwatch,
+    Refresh,
+    History,
+    // watches the same section in the next semester, from a notice that a watch expired
+    Rewatch,
+}
+
+impl Action {
+    fn id(&self) -> &'static str {
+        match self {
+            Action::Watch => "watch",
+            Action::Unwatch => "unwatch",
+            Action::Refresh => "refresh",
+            Action::History => "history",
+            Action::Rewatch => "rewatch",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Action> {
+        match id {
+            "watch" => Some(Action::Watch),
+            "unwatch" => Some(Action::Unwatch),
+            "refresh" => Some(Action::Refresh),
+            "history" => Some(Action::History),
+            "rewatch" => Some(Action::Rewatch),
+            _ => None,
+        }
+    }
+
+    fn label(&self, locale: Locale) -> String {
+        tr!(
+            locale,
+            match self {
+                Action::Watch => "button-watch",
+                Action::Unwatch => "button-unwatch",
+                Action::Refresh => "button-refresh",
+                Action::History => "button-history",
+                Action::Rewatch => "button-rewatch",
+            }
+        )
+    }
+}
+
+// if there are multiple classes in the message, `section` distinguishes which row belongs to which
+pub fn class_buttons<'a>(
+    r: &'a mut CreateActionRow,
+    locale: Locale,
+    query: &Query,
+    section: Option<&str>,
+) -> &'a mut CreateActionRow {
+    for action in [
+        Action::Watch,
+        Action::Unwatch,
+        Action::Refresh,
+        Action::History,
+    ] {
+        r.create_button(|b| {
+            b.custom_id(custom_id(action, query))
+                .label(match section {
+                    Some(section) => format!("{} {}", action.label(locale), section),
+                    None => action.label(locale),
+                })
+                .style(match action {
+                    Action::Watch => ButtonStyle::Primary,
+                    _ => ButtonStyle::Secondary,
+                })
+        });
+    }
+    r
+}
+
+pub fn rewatch_button<'a>(
+    r: &'a mut CreateActionRow,
+    locale: Locale,
+    query: &Query,
+) -> &'a mut CreateActionRow {
+    r.create_button(|b| {
+        b.custom_id(custom_id(Action::Rewatch, query))
+            .label(format!(
+                "{} {} {}",
+                Action::Rewatch.label(locale),
+                catalog::course_name(&query.course).unwrap_or(&query.course),
+                query.section
+            ))
+            .style(ButtonStyle::Primary)
+    })
+}
+
+pub async fn handle(
+    ctx: &Context,
+    press: &MessageComponentInteraction,
+    data: &Data,
+) -> Result<(), crate::Error> {
+    let (action, query) = match parse_custom_id(&press.data.custom_id) {
+        Some(parsed) => parsed,
+        None => return Ok(()),
+    };
+    let locale = Locale::new(&data.translations, Some(&press.locale));
+    if data.blocklist.is_blocked(press.user.id).await? {
+        return reply(ctx, press, tr!(locale, "blocked")).await;
+    }
+
+    match action {
+        Action::Watch => {
+            let added = data
+                .watcher
+                .add_watch(
+                    Recipient {
+                        user_id: press.user.id,
+                        guild_id: press.guild_id,
+                    },
+                    &query,
+                    None,
+                )
+                .await;
+            if let Err(err) = added {
+                let content = render::watch_error(locale, &err).ok_or(err)?;
+                return reply(ctx, press, content).await;
+            }
+            data.watcher
+                .settings()
+                .set_locale(press.user.id, &press.locale)
+                .await?;
+            reply(
+                ctx,
+                press,
+                tr!(locale, "watch-added", class: describe(locale, &query)),
+            )
+            .await?;
+        }
+        Action::Rewatch => {
+            // the next semester's schedule may have to be fetched from UB first
+            press
+                .create_interaction_response(ctx, |r| {
+                    r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                        .interaction_response_data(|d| d.ephemeral(true))
+                })
+                .await?;
+            let content = rewatch(data, press, locale, &query).await?;
+            press
+                .edit_original_interaction_response(ctx, |r| r.content(content))
+                .await?;
+        }
+        Action::Unwatch => {
+            let content = if data.watcher.remove_watch(press.user.id, &query).await? {
+                tr!(locale, "watch-removed", class: describe(locale, &query))
+            } else {
+                tr!(locale, "watch-missing", class: describe(locale, &query))
+            };
+            reply(ctx, press, content).await?;
+        }
+        Action::Refresh => {
+            if let Some(left) = data.refresh_cooldown.hit(press.user.id) {
+                let content = tr!(locale, "refresh-cooldown", seconds: left.as_secs() + 1);
+                return reply(ctx, press, content).await;
+            }
+
+            // messages holding a digest of multiple classes are left alone, and the class is only
+            // shown to the user
+            let single = press.message.embeds.len() == 1;
+            // fetching from UB can take longer than Discord waits for a response
+            press
+                .create_interaction_response(ctx, |r| {
+                    if single {
+                        r.kind(InteractionResponseType::DeferredUpdateMessage)
+                    } else {
+                        r.kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                            .interaction_response_data(|d| d.ephemeral(true))
+                    }
+                })
+                .await?;
+
+            // goes through the watcher so that any change is still delivered to its watchers
+            let record = match data.watcher.check(query.clone(), MAX_AGE).await? {
+                Check::Old(record) => record,
+                Check::New(notifier) => notifier.into_new_record(),
+            };
+            press
+                .edit_original_interaction_response(ctx, |r| {
+                    r.embed(|e| render::class_embed(e, locale, &query, &record))
+                })
+                .await?;
+        }
+        Action::History => {
+            let history = data.watcher.cache().history(&query, HISTORY_LIMIT).await?;
+            press
+                .create_interaction_response(ctx, |r| {
+                    r.kind(InteractionResponseType::ChannelMessageWithSource)
+                        .interaction_response_data(|d| {
+                            d.embed(|e| {
+                                render::history_embed(e, locale, &query, &history, HISTORY_ENTRIES)
+                            })
+                            .ephemeral(true)
+                        })
+                })
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+// the section is only watched if it's offered again, otherwise the user is pointed to the others
+async fn rewatch(
+    data: &Data,
+    press: &MessageComponentInteraction,
+    locale: Locale<'_>,
+    query: &Query,
+) -> Result<String, crate::Error> {
+    let course = catalog::course_name(&query.course).unwrap_or(&query.course);
+    let semester = match catalog::next_semester(&query.semester) {
+        Some(semester) => semester,
+        None => return Ok(tr!(locale, "rewatch-unknown", course: course)),
+    };
+    let next_query = Query::from_ids(
+        query.course.clone(),
+        semester.id().to_owned(),
+        query.career.clone(),
+        query.section.clone(),
+    );
+    let semester_name =
+        catalog::semester_name(&next_query.semester).unwrap_or(next_query.semester.clone());
+
+    let schedule = data
+        .catalog
+        .schedule(Course::Raw(query.course.clone()), semester)
+        .await?;
+    if schedule.groups.is_empty() {
+        return Ok(tr!(
+            locale,
+            "course-not-found",
+            course: course,
+            semester: semester_name
+        ));
+    }
+    if schedule.class(&query.section).is_none() {
+        return Ok(tr!(
+            locale,
+            "rewatch-missing-section",
+            course: course,
+            section: query.section.as_str(),
+            semester: semester_name,
+            sections: schedule.sections().collect::<Vec<_>>().join(", ")
+        ));
+    }
+
+    let added = data
+        .watcher
+        .add_watch(
+            Recipient {
+                user_id: press.user.id,
+                guild_id: press.guild_id,
+            },
+            &next_query,
+            None,
+        )
+        .await;
+    if let Err(err) = added {
+        return Ok(render::watch_error(locale, &err).ok_or(err)?);
+    }
+    data.watcher
+        .settings()
+        .set_locale(press.user.id, &press.locale)
+        .await?;
+
+    Ok(tr!(
+        locale,
+        "watch-added",
+        class: describe(locale, &next_query)
+    ))
+}
+
+async fn reply(
+    ctx: &Context,
+    press: &MessageComponentInteraction,
+    content: String,
+) -> Result<(), crate::Error> {
+    press
+        .create_interaction_response(ctx, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|d| d.content(content).ephemeral(true))
+        })
+        .await?;
+    Ok(())
+}
+
+fn custom_id(action: Action, query: &Query) -> String {
+    format!(
+        "{PREFIX}:{}:{}:{}:{}:{}",
+        action.id(),
+        query.course,
+        query.semester,
+        query.career,
+        query.section
+    )
+}
+
+fn parse_custom_id(custom_id: &str) -> Option<(Action, Query)> {
+    let mut parts = custom_id.splitn(6, ':');
+    if parts.next()? != PREFIX {
+        return None;
+    }
+
+    let action = Action::from_id(parts.next()?)?;
+    Some((
+        action,
+        Query::from_ids(
+            parts.next()?.to_owned(),
+            parts.next()?.to_owned(),
+            parts.next()?.to_owned(),
+            parts.next()?.to_owned(),
+        ),
+    ))
+}
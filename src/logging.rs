@@ -0,0 +1,79 @@
+use std::env;
+
+use poise::{
+    serenity_prelude::CommandDataOption, ApplicationContext, BoxFuture, Command, FrameworkError,
+};
+use tracing::{info_span, level_filters::LevelFilter, warn, Instrument};
+use tracing_subscriber::EnvFilter;
+
+use crate::{Data, Error};
+
+type SlashAction = for<'a> fn(
+    ApplicationContext<'a, Data, Error>,
+) -> BoxFuture<'a, Result<(), FrameworkError<'a, Data, Error>>>;
+
+// used when `RUST_LOG` isn't set, e.g. `RUST_LOG=ubs_bot=debug,warn` to debug the bot alone
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::INFO;
+
+// `LOG_FORMAT=json` for log aggregators, otherwise logs are pretty printed
+pub fn init() {
+    let filter = EnvFilter::builder()
+        .with_default_directive(DEFAULT_LEVEL.into())
+        .from_env_lossy();
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+
+    let format = env::var("LOG_FORMAT").ok();
+    match format.as_deref() {
+        Some("json") => builder
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .init(),
+        _ => builder.pretty().init(),
+    }
+
+    if let Some(format) = format.filter(|x| x != "json" && x != "pretty") {
+        warn!(
+            "unknown log format `{}`, expected `pretty` or `json`",
+            format
+        );
+    }
+}
+
+// every command runs within a span of who invoked it and with what, the generated action is moved
+// into `custom_data` and wrapped, so that no command can be left without one
+pub fn trace_commands(commands: &mut [Command<Data, Error>]) {
+    for command in commands {
+        if let Some(action) = command.slash_action.replace(traced as SlashAction) {
+            command.custom_data = Box::new(action);
+        }
+        trace_commands(&mut command.subcommands);
+    }
+}
+
+fn traced(
+    ctx: ApplicationContext<'_, Data, Error>,
+) -> BoxFuture<'_, Result<(), FrameworkError<'_, Data, Error>>> {
+    Box::pin(async move {
+        let context = poise::Context::Application(ctx);
+        let span = info_span!(
+            "command",
+            name = %ctx.command.qualified_name,
+            user = %context.author().id,
+            guild = ?context.guild_id(),
+            options = %options(ctx.args),
+        );
+        match ctx.command.custom_data.downcast_ref::<SlashAction>() {
+            Some(action) => action(ctx).instrument(span).await,
+            None => Ok(()),
+        }
+    })
+}
+
+// e.g. `course="CSE115" section="A1"`
+fn options(args: &[CommandDataOption]) -> String {
+    args.iter()
+        .filter_map(|x| Some(format!("{}={}", x.name, x.value.as_ref()?)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
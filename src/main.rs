@@ -1,29 +1,97 @@
+mod academic;
+mod archive;
+mod blocklist;
 mod cache;
+mod calendar;
+mod catalog;
 mod commands;
+mod cooldown;
+mod expiry;
+#[cfg(test)]
+mod fixtures;
+mod interactions;
+mod logging;
+mod meeting;
+mod metrics;
 mod notifier;
+mod outbox;
+mod planner;
+mod priority;
+mod render;
+mod server;
+mod settings;
+mod translation;
 mod watcher;
 
-use std::{env, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
+use academic::AcademicCalendar;
+use archive::Archive;
+use blocklist::Blocklist;
 use cache::Cache;
-use poise::{serenity_prelude::GatewayIntents, Framework, FrameworkOptions};
+use catalog::Catalog;
+use cooldown::Cooldown;
+use metrics::Metrics;
+use poise::{
+    serenity_prelude::{Command, CommandId, GatewayIntents, Interaction, UserId},
+    CooldownConfig, CooldownTracker, Event, Framework, FrameworkOptions,
+};
 
-use sqlx::PgPool;
-use tracing::error;
-use watcher::Watcher;
+use outbox::Outbox;
+use server::ServerState;
+use settings::Settings;
+use sqlx::{migrate::Migrator, types::chrono, PgPool};
+use tokio::signal;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::{error, info};
+use translation::{tr, Locale, Translations};
+use watcher::{Limits, Watcher};
 
 const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+// how often a class is polled before its priority is taken into account
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
 const MAX_AGE: Duration = Duration::from_secs(1);
+const DELIVERY_INTERVAL: Duration = Duration::from_secs(5);
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+// delivered notifications are kept this long in case something needs to be looked into
+const RETENTION_DAYS: i64 = 7;
+// as are notifications that ran out of attempts, for longer since they were never seen
+const DEAD_RETENTION_DAYS: i64 = 30;
+// the watcher is stalled if it hasn't ticked, or recovered from a failed cycle, within this long
+const CHECK_WINDOW: Duration = Duration::from_secs(5 * 60);
+const DEFAULT_USER_WATCHES: i64 = 25;
+const DEFAULT_GUILD_WATCHES: i64 = 500;
+const DEFAULT_COMMAND_COOLDOWN: i64 = 10;
+// commands that fetch from UB on every use, rather than going through the cache, the Refresh
+// button shares the same cooldown
+const COOLDOWN_COMMANDS: &[&str] = &["info", "rawinfo"];
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
 
 type Error = Box<dyn std::error::Error + Send + Sync>;
 type Context<'a> = poise::Context<'a, Data, Error>;
 
 pub struct Data {
     watcher: Arc<Watcher>,
+    // registered commands by name, so that replies can mention them
+    command_ids: HashMap<String, CommandId>,
+    catalog: Arc<Catalog>,
+    calendar: Arc<AcademicCalendar>,
+    blocklist: Blocklist,
+    translations: Arc<Translations>,
+    refresh_cooldown: Cooldown,
 }
 
 #[tokio::main]
 async fn main() {
+    logging::init();
+
     let token = env::var("DISCORD_TOKEN").expect("missing `DISCORD_TOKEN` environment variable");
 
     // let pool = PgPool::connect(&env::var("DATABASE_URL").unwrap())
@@ -31,47 +99,204 @@ async fn main() {
         .await
         .expect("failed to connect to database");
 
-    sqlx::migrate!("./migrations")
+    MIGRATOR
         .run(&database)
         .await
         .expect("failed to migrate database");
 
-    let cache = Cache::new(database);
-    let watcher = Arc::new(Watcher::new(cache));
+    let translations = Arc::new(Translations::load().expect("failed to load translations"));
+
+    let settings = Settings::new(database.clone());
+    let outbox = Outbox::new(database.clone());
+    let archive = Archive::new(database.clone());
+    let metrics = Arc::new(Metrics::new().expect("failed to create metrics"));
+    let cache = Cache::new(database.clone(), metrics);
+    // e.g. `ACADEMIC_CALENDAR=calendar.toml`, otherwise dates are approximated
+    let calendar = Arc::new(match env::var("ACADEMIC_CALENDAR") {
+        Ok(path) => {
+            AcademicCalendar::load(Path::new(&path)).expect("failed to load academic calendar")
+        }
+        Err(_) => AcademicCalendar::default(),
+    });
+    let limits = Limits {
+        user: env_limit("MAX_USER_WATCHES", DEFAULT_USER_WATCHES),
+        guild: env_limit("MAX_GUILD_WATCHES", DEFAULT_GUILD_WATCHES),
+    };
+    let watcher = Arc::new(Watcher::new(
+        cache,
+        settings,
+        outbox,
+        archive,
+        translations.clone(),
+        calendar.clone(),
+        limits,
+    ));
+    let blocklist = Blocklist::new(database.clone());
+
+    // every task is waited on before exiting, so that nothing is cut off mid-write
+    let shutdown = CancellationToken::new();
+    let tasks = TaskTracker::new();
 
+    let server_watcher = watcher.clone();
     let loop_watcher = watcher.clone();
-    tokio::spawn(async move {
-        loop_watcher.watch(UPDATE_INTERVAL, MAX_AGE).await;
+    let loop_shutdown = shutdown.clone();
+    tasks.spawn(async move {
+        loop_watcher
+            .watch(UPDATE_INTERVAL, POLL_INTERVAL, MAX_AGE, loop_shutdown)
+            .await;
+    });
+
+    let prune_watcher = watcher.clone();
+    let prune_shutdown = shutdown.clone();
+    tasks.spawn(async move {
+        prune_watcher
+            .prune(
+                PRUNE_INTERVAL,
+                chrono::Duration::days(RETENTION_DAYS),
+                chrono::Duration::days(DEAD_RETENTION_DAYS),
+                prune_shutdown,
+            )
+            .await;
     });
 
+    let drain_tasks = tasks.clone();
+    let drain_shutdown = shutdown.clone();
+
+    let mut commands = vec![
+        commands::info(),
+        commands::rawinfo(),
+        commands::watch(),
+        commands::unwatch(),
+        commands::snooze(),
+        commands::config(),
+        commands::preferences(),
+        commands::search(),
+        commands::course(),
+        commands::calendar(),
+        commands::conflicts(),
+        commands::plan(),
+        commands::admin(),
+    ];
+    translation::localize_commands(&translations, &mut commands);
+    logging::trace_commands(&mut commands);
+
+    // e.g. `COMMAND_COOLDOWN=30`, in seconds per user, 0 to disable
+    let cooldown = env_limit("COMMAND_COOLDOWN", DEFAULT_COMMAND_COOLDOWN) as u64;
+    for command in commands
+        .iter_mut()
+        .filter(|x| COOLDOWN_COMMANDS.contains(&x.name.as_str()))
+    {
+        command.cooldowns = Mutex::new(CooldownTracker::new(CooldownConfig {
+            user: (cooldown > 0).then(|| Duration::from_secs(cooldown)),
+            ..Default::default()
+        }));
+    }
+
+    // e.g. `OWNERS=123,456`, allowed to use `/admin` along with the owner of the application
+    let owners = env::var("OWNERS")
+        .unwrap_or_default()
+        .split(',')
+        .filter(|x| !x.trim().is_empty())
+        .map(|x| x.trim().parse().map(UserId))
+        .collect::<Result<HashSet<_>, _>>()
+        .expect("invalid `OWNERS` environment variable");
+
     let framework = Framework::builder()
         .token(token)
         .intents(GatewayIntents::GUILD_MESSAGES | GatewayIntents::MESSAGE_CONTENT)
         .options(FrameworkOptions {
-            commands: vec![
-                commands::info(),
-                commands::rawinfo(),
-                commands::watch(),
-                commands::unwatch(),
-            ],
+            commands,
+            owners,
+            // blocked users are told once per command rather than left wondering
+            command_check: Some(|ctx| {
+                Box::pin(async move {
+                    if !ctx.data().blocklist.is_blocked(ctx.author().id).await? {
+                        return Ok(true);
+                    }
+                    ctx.send(|f| {
+                        f.content(tr!(Locale::from_ctx(ctx), "blocked"))
+                            .ephemeral(true)
+                    })
+                    .await?;
+                    Ok(false)
+                })
+            }),
+            event_handler: |ctx, event, _framework, data| {
+                Box::pin(async move {
+                    if let Event::InteractionCreate {
+                        interaction: Interaction::MessageComponent(press),
+                    } = event
+                    {
+                        interactions::handle(ctx, press, data).await?;
+                    }
+                    Ok(())
+                })
+            },
             ..Default::default()
         })
         .setup(move |ctx, _ready, framework| {
             Box::pin(async move {
-                poise::builtins::register_globally(ctx, &framework.options().commands).await?;
-                Ok(Data { watcher })
+                let create_commands =
+                    poise::builtins::create_application_commands(&framework.options().commands);
+                let command_ids = Command::set_global_application_commands(ctx, |b| {
+                    *b = create_commands;
+                    b
+                })
+                .await?
+                .into_iter()
+                .map(|x| (x.name, x.id))
+                .collect();
+
+                let drain_watcher = watcher.clone();
+                let http = ctx.http.clone();
+                drain_tasks.spawn(async move {
+                    drain_watcher
+                        .drain(&http, DELIVERY_INTERVAL, drain_shutdown)
+                        .await;
+                });
+
+                Ok(Data {
+                    watcher,
+                    command_ids,
+                    catalog: Arc::new(Catalog::new()),
+                    calendar,
+                    blocklist,
+                    translations,
+                    refresh_cooldown: Cooldown::new(Duration::from_secs(cooldown)),
+                })
             })
         })
         .build()
         .await
         .expect("TODO");
 
+    // e.g. `HTTP_ADDR=0.0.0.0:9090`, the server is only started if set
+    if let Ok(addr) = env::var("HTTP_ADDR") {
+        let addr = addr
+            .parse()
+            .expect("invalid `HTTP_ADDR` environment variable");
+        let state = ServerState {
+            watcher: server_watcher,
+            shard_manager: framework.shard_manager(),
+            migrator: &MIGRATOR,
+            window: CHECK_WINDOW,
+            started: Instant::now(),
+        };
+        let server_shutdown = shutdown.clone();
+        tasks.spawn(async move {
+            if let Err(err) = server::serve(addr, state, server_shutdown).await {
+                error!("server error: {:?}", err);
+            }
+        });
+    }
+
     {
         let framework = framework.clone();
+        let shutdown = shutdown.clone();
         tokio::spawn(async move {
-            tokio::signal::ctrl_c()
-                .await
-                .expect("Could not register ctrl+c handler");
+            shutdown_signal().await;
+            info!("shutting down");
+            shutdown.cancel();
             framework.shard_manager().lock().await.shutdown_all().await;
         });
     }
@@ -79,4 +304,50 @@ async fn main() {
     if let Err(why) = framework.start_autosharded().await {
         error!("Client error: {:?}", why);
     }
+
+    // the client also stops if it fails to connect, in which case nothing else was told to stop
+    shutdown.cancel();
+    tasks.close();
+    tasks.wait().await;
+    database.close().await;
+}
+
+impl Data {
+    // clicking a mention opens the command, falling back to plain text if it isn't registered
+    pub fn command_mention(&self, name: &str) -> String {
+        match self.command_ids.get(name) {
+            Some(id) => format!("</{name}:{id}>"),
+            None => format!("`/{name}`"),
+        }
+    }
+}
+
+// e.g. `MAX_USER_WATCHES=50`, falling back to the default if unset, also used for other counts
+fn env_limit(name: &str, default: i64) -> i64 {
+    match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .ok()
+            .filter(|x: &i64| *x >= 0)
+            .unwrap_or_else(|| panic!("invalid `{name}` environment variable")),
+        Err(_) => default,
+    }
+}
+
+// ctrl-c from a terminal, or SIGTERM from a process manager or container runtime
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to register SIGTERM handler");
+        tokio::select! {
+            result = signal::ctrl_c() => result.expect("failed to register ctrl-c handler"),
+            _ = terminate.recv() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    signal::ctrl_c()
+        .await
+        .expect("failed to register ctrl-c handler");
 }
@@ -0,0 +1,110 @@
+use sqlx::types::chrono::{NaiveTime, Weekday};
+use ubs_lib::model::ClassModel;
+
+// when a class meets each week, in UB local time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Meeting {
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl Meeting {
+    // `None` if the class doesn't meet at a set time, e.g. online classes
+    pub fn of(class: &ClassModel) -> Option<Meeting> {
+        let days = class
+            .days_of_week
+            .iter()
+            .flatten()
+            .flatten()
+            .filter_map(|x| x.to_string().parse::<Weekday>().ok())
+            .collect::<Vec<_>>();
+        if days.is_empty() {
+            return None;
+        }
+
+        Some(Meeting {
+            days,
+            start: class.start_time?,
+            end: class.end_time?,
+        })
+    }
+
+    // classes that end exactly when another starts don't overlap
+    pub fn overlaps(&self, other: &Meeting) -> bool {
+        self.days.iter().any(|x| other.days.contains(x))
+            && self.start < other.end
+            && other.start < self.end
+    }
+}
+
+// every pair of classes that overlap, by their index
+pub fn conflicts<'a>(classes: impl IntoIterator<Item = &'a ClassModel>) -> Vec<(usize, usize)> {
+    let meetings = classes.into_iter().map(Meeting::of).collect::<Vec<_>>();
+    let mut conflicts = Vec::new();
+    for (i, first) in meetings.iter().enumerate() {
+        for (j, second) in meetings.iter().enumerate().skip(i + 1) {
+            if let (Some(first), Some(second)) = (first, second) {
+                if first.overlaps(second) {
+                    conflicts.push((i, j));
+                }
+            }
+        }
+    }
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::class;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn meeting(days: &[Weekday], start: NaiveTime, end: NaiveTime) -> Meeting {
+        Meeting {
+            days: days.to_vec(),
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn overlapping_meetings() {
+        let first = meeting(&[Weekday::Mon, Weekday::Wed], time(9, 0), time(9, 50));
+        let second = meeting(&[Weekday::Wed], time(9, 30), time(10, 20));
+        assert!(first.overlaps(&second));
+        assert!(second.overlaps(&first));
+    }
+
+    #[test]
+    fn back_to_back_meetings() {
+        let first = meeting(&[Weekday::Mon], time(9, 0), time(9, 50));
+        let second = meeting(&[Weekday::Mon], time(9, 50), time(10, 40));
+        assert!(!first.overlaps(&second));
+    }
+
+    #[test]
+    fn meetings_on_different_days() {
+        let first = meeting(&[Weekday::Mon], time(9, 0), time(9, 50));
+        let second = meeting(&[Weekday::Tue], time(9, 0), time(9, 50));
+        assert!(!first.overlaps(&second));
+    }
+
+    #[test]
+    fn conflicts_by_index() {
+        let mut online = class("A4", "Lecture", &["Monday"], "09:00", "09:50");
+        online.start_time = None;
+        let classes = [
+            class("A1", "Lecture", &["Monday"], "09:00", "09:50"),
+            class("A2", "Lecture", &["Tuesday"], "09:00", "09:50"),
+            class("A3", "Lecture", &["Monday"], "09:30", "10:20"),
+            // doesn't meet at a set time, so it never conflicts
+            online,
+        ];
+        assert_eq!(conflicts(&classes), vec![(0, 2)]);
+        assert_eq!(Meeting::of(&classes[3]), None);
+    }
+}
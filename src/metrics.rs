@@ -0,0 +1,87 @@
+use prometheus::{
+    exponential_buckets, Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, Opts,
+    Registry, TextEncoder,
+};
+use sqlx::PgPool;
+
+// https://prometheus.io/docs/practices/naming/
+const NAMESPACE: &str = "ubs_bot";
+
+#[derive(Debug)]
+pub struct Metrics {
+    registry: Registry,
+    // classes fetched from UB, cache hits aren't counted, `rate()` gives polls per second
+    pub polls: IntCounter,
+    pub fetch_duration: Histogram,
+    // labelled by `FetchClassError::kind`
+    pub fetch_errors: IntCounterVec,
+    // labelled by whether the notification was "sent", "failed" and will be retried, or "dead"
+    // after running out of attempts
+    pub notifications: IntCounterVec,
+    // read from the database whenever metrics are gathered
+    watches: IntGauge,
+    // counted by the watcher after cycles that poll classes rather than on every scrape
+    pub cache_rows: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Metrics, prometheus::Error> {
+        let registry = Registry::new_custom(Some(NAMESPACE.to_owned()), None)?;
+        let metrics = Metrics {
+            polls: IntCounter::new("polls_total", "Classes fetched from UB")?,
+            fetch_duration: Histogram::with_opts(
+                HistogramOpts::new(
+                    "fetch_duration_seconds",
+                    "Time taken to fetch a class from UB",
+                )
+                // fetches go through several pages, so they take seconds rather than milliseconds
+                .buckets(exponential_buckets(0.1, 2.0, 10)?),
+            )?,
+            fetch_errors: IntCounterVec::new(
+                Opts::new("fetch_errors_total", "Errors fetching a class from UB"),
+                &["kind"],
+            )?,
+            notifications: IntCounterVec::new(
+                Opts::new("notifications_total", "Notifications delivered to users"),
+                &["result"],
+            )?,
+            watches: IntGauge::new("watches", "Watches that aren't suspended")?,
+            cache_rows: IntGauge::new("cache_rows", "Snapshots stored in the cache table")?,
+            registry,
+        };
+
+        metrics.registry.register(Box::new(metrics.polls.clone()))?;
+        metrics
+            .registry
+            .register(Box::new(metrics.fetch_duration.clone()))?;
+        metrics
+            .registry
+            .register(Box::new(metrics.fetch_errors.clone()))?;
+        metrics
+            .registry
+            .register(Box::new(metrics.notifications.clone()))?;
+        metrics
+            .registry
+            .register(Box::new(metrics.watches.clone()))?;
+        metrics
+            .registry
+            .register(Box::new(metrics.cache_rows.clone()))?;
+        Ok(metrics)
+    }
+
+    // in the prometheus text format
+    pub async fn gather(&self, database: &PgPool) -> Result<String, crate::Error> {
+        let watches = sqlx::query!(
+            r#"
+SELECT COUNT(*) AS "count!"
+FROM watchers
+WHERE NOT suspended;
+            "#
+        )
+        .fetch_one(database)
+        .await?;
+        self.watches.set(watches.count);
+
+        Ok(TextEncoder::new().encode_to_string(&self.registry.gather())?)
+    }
+}
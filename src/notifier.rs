@@ -1,31 +1,54 @@
-use poise::serenity_prelude::{ChannelId, CreateMessage, Http, UserId};
+use poise::serenity_prelude::{
+    ChannelId, CreateEmbed, CreateMessage, GuildId, Http, HttpError, Mentionable, SerenityError,
+    UserId,
+};
 
 use crate::{
     cache::{ClassRecord, Query},
+    interactions, render,
+    translation::{tr, Locale},
     Context,
 };
 
-const TIME_FORMAT: &str = "%-I:%M%p";
-const UNKNOWN_FIELD: &str = "[unknown]";
+// https://discord.com/developers/docs/topics/opcodes-and-status-codes#json
+const CANNOT_MESSAGE_USER: isize = 50007;
+// each embed gets its own row of buttons, and messages are limited to 5 rows
+// https://discord.com/developers/docs/interactions/message-components#action-rows
+const MAX_EMBEDS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Recipient {
+    pub user_id: UserId,
+    // `None` if the class was watched from direct messages
+    pub guild_id: Option<GuildId>,
+}
 
 #[derive(Debug)]
 pub struct Notifier {
     new: ClassRecord,
-    user_ids: Vec<UserId>,
+    recipients: Vec<Recipient>,
     old: Option<ClassRecord>,
     query: Query,
 }
 
+// all of the notifiers for a single user during a check, sent together
+#[derive(Debug, Default)]
+pub struct Digest<'a> {
+    notifiers: Vec<&'a Notifier>,
+    // watches whose semester is over, sent as a single notice after any changes
+    expired: Vec<&'a Query>,
+}
+
 impl Notifier {
     pub(crate) fn new(
         new: ClassRecord,
-        user_ids: Vec<UserId>,
+        recipients: Vec<Recipient>,
         query: Query,
         old: Option<ClassRecord>,
     ) -> Notifier {
         Notifier {
             new,
-            user_ids,
+            recipients,
             query,
             old,
         }
@@ -38,119 +61,138 @@ impl Notifier {
         &self.new
     }
 
+    pub fn into_new_record(self) -> ClassRecord {
+        self.new
+    }
+
+    pub fn recipients(&self) -> &[Recipient] {
+        &self.recipients
+    }
+
     pub async fn notify_reply(&self, ctx: Context<'_>) -> Result<(), crate::Error> {
         Ok(())
     }
 
-    // TODO: add more context about the old/new changes
-    pub async fn notify(&self, http: &Http, channel: ChannelId) -> Result<(), crate::Error> {
+    pub fn embed<'a>(&self, e: &'a mut CreateEmbed, locale: Locale) -> &'a mut CreateEmbed {
+        render::class_embed(e, locale, &self.query, &self.new);
         if let Some(old) = &self.old {
+            e.description(render::class_changes(locale, &old.model, &self.new.model));
+        }
+        e
+    }
+}
+
+impl<'a> Digest<'a> {
+    pub fn push(&mut self, notifier: &'a Notifier) {
+        self.notifiers.push(notifier);
+    }
+
+    pub fn push_expired(&mut self, query: &'a Query) {
+        self.expired.push(query);
+    }
+
+    pub fn extend(&mut self, other: &Digest<'a>) {
+        self.notifiers.extend(&other.notifiers);
+        self.expired.extend(&other.expired);
+    }
+
+    pub async fn notify_dm(
+        &self,
+        http: &Http,
+        locale: Locale<'_>,
+        user_id: UserId,
+    ) -> Result<(), SerenityError> {
+        let channel = user_id.create_dm_channel(http).await?;
+        self.notify(http, locale, channel.id, None).await
+    }
+
+    // paginated so that each message stays within the embed limit
+    pub async fn notify(
+        &self,
+        http: &Http,
+        locale: Locale<'_>,
+        channel: ChannelId,
+        user_id: Option<UserId>,
+    ) -> Result<(), SerenityError> {
+        for page in self.notifiers.chunks(MAX_EMBEDS) {
             channel
                 .send_message(http, |f| {
-                    info_msg_users(f, &self.query, old, &self.user_ids)
+                    for notifier in page {
+                        f.add_embed(|e| notifier.embed(e, locale));
+                    }
+                    f.components(|c| {
+                        for notifier in page {
+                            c.create_action_row(|r| {
+                                interactions::class_buttons(
+                                    r,
+                                    locale,
+                                    &notifier.query,
+                                    (page.len() > 1)
+                                        .then_some(notifier.new.model.section.as_deref())
+                                        .flatten(),
+                                )
+                            });
+                        }
+                        c
+                    });
+                    mention_user(f, user_id)
+                })
+                .await?;
+        }
+
+        // buttons are the only thing in the notice, so each page can hold a row per watch
+        for page in self.expired.chunks(MAX_EMBEDS) {
+            let notice = tr!(
+                locale,
+                "watch-expired",
+                classes: page
+                    .iter()
+                    .map(|x| format!("- {}", render::describe(locale, x)))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+            channel
+                .send_message(http, |f| {
+                    mention_user(f, user_id)
+                        .content(match user_id {
+                            Some(user_id) => format!("{} {notice}", user_id.mention()),
+                            None => notice.clone(),
+                        })
+                        .components(|c| {
+                            for query in page {
+                                c.create_action_row(|r| {
+                                    interactions::rewatch_button(r, locale, query)
+                                });
+                            }
+                            c
+                        })
                 })
                 .await?;
         }
-        channel
-            .send_message(http, |f| {
-                info_msg_users(f, &self.query, &self.new, &self.user_ids)
-            })
-            .await?;
 
         Ok(())
     }
 }
 
-fn info_msg_users<'a, 'b>(
-    f: &'a mut CreateMessage<'b>,
-    query: &Query,
-    record: &ClassRecord,
-    user_ids: &[UserId],
-) -> &'a mut CreateMessage<'b> {
-    info_msg(f, query, record).allowed_mentions(|am| am.empty_parse().users(user_ids))
+// the user either closed their direct messages or blocked the bot
+pub fn is_dm_closed(err: &SerenityError) -> bool {
+    match err {
+        SerenityError::Http(err) => match **err {
+            HttpError::UnsuccessfulRequest(ref res) => res.error.code == CANNOT_MESSAGE_USER,
+            _ => false,
+        },
+        _ => false,
+    }
 }
 
-fn info_msg<'a, 'b>(
+fn mention_user<'a, 'b>(
     f: &'a mut CreateMessage<'b>,
-    query: &Query,
-    record: &ClassRecord,
+    user_id: Option<UserId>,
 ) -> &'a mut CreateMessage<'b> {
-    let model = &record.model;
-    f.embed(|e| {
-        e.title(format!("{} - {}", query.course, query.semester))
-            .author(|a| a.name(model.instructor.as_deref().unwrap_or(UNKNOWN_FIELD)))
-            .timestamp(record.timestamp)
-            // .field("Id", model.class_id.unwrap(), true)
-            .field(
-                "Section",
-                model.section.as_deref().unwrap_or(UNKNOWN_FIELD),
-                true,
-            )
-            .field(
-                "Type",
-                model
-                    .class_type
-                    .map(|x| x.to_string())
-                    .as_deref()
-                    .unwrap_or(UNKNOWN_FIELD),
-                true,
-            )
-            .field("Room", model.room.as_deref().unwrap_or(UNKNOWN_FIELD), true)
-            .field(
-                "Open",
-                model
-                    .is_open
-                    .map(|x| x.to_string())
-                    .as_deref()
-                    .unwrap_or(UNKNOWN_FIELD),
-                true,
-            )
-            .field(
-                "Seats",
-                format!(
-                    "{}/{}",
-                    model
-                        .open_seats
-                        .map(|x| x.to_string())
-                        .as_deref()
-                        .unwrap_or(UNKNOWN_FIELD),
-                    model
-                        .total_seats
-                        .map(|x| x.to_string())
-                        .as_deref()
-                        .unwrap_or(UNKNOWN_FIELD),
-                ),
-                true,
-            )
-            .field(
-                // TODO: dynamically set plurality
-                "Day(s) of Week",
-                match model.days_of_week {
-                    Some(ref dow) => dow
-                        .iter()
-                        .map(|x| x.map(|y| y.to_string()).unwrap_or(UNKNOWN_FIELD.to_owned()))
-                        .collect::<Vec<String>>()
-                        .join(", "),
-                    None => UNKNOWN_FIELD.to_owned(),
-                },
-                false,
-            )
-            .field(
-                "Time",
-                format!(
-                    "{} — {}",
-                    model
-                        .start_time
-                        .map(|x| x.format(TIME_FORMAT).to_string())
-                        .as_deref()
-                        .unwrap_or(UNKNOWN_FIELD),
-                    model
-                        .end_time
-                        .map(|x| x.format(TIME_FORMAT).to_string())
-                        .as_deref()
-                        .unwrap_or(UNKNOWN_FIELD),
-                ),
-                true,
-            )
-    })
+    match user_id {
+        Some(user_id) => f
+            .content(user_id.mention())
+            .allowed_mentions(|am| am.empty_parse().users([user_id])),
+        None => f.allowed_mentions(|am| am.empty_parse()),
+    }
 }
@@ -0,0 +1,280 @@
+use poise::serenity_prelude::{GuildId, UserId};
+use sqlx::{
+    types::{
+        chrono::{DateTime, Duration, Utc},
+        Json,
+    },
+    PgConnection, PgPool,
+};
+use ubs_lib::model::ClassModel;
+
+use crate::{
+    cache::{ClassRecord, Query},
+    notifier::{Notifier, Recipient},
+};
+
+// failed deliveries are retried with an exponential backoff until this many attempts, after which
+// they're marked dead
+pub const MAX_ATTEMPTS: i32 = 10;
+// pending rows are claimed for this long, if the delivery is never finished, e.g. the bot crashed,
+// then they're picked up again afterwards
+pub const CLAIM_DURATION: Duration = Duration::minutes(5);
+
+#[derive(Debug)]
+pub struct Pending {
+    pub id: i64,
+    pub notifier: Box<Notifier>,
+}
+
+// notifications are written alongside the cache snapshot that triggered them, so that nothing is
+// lost if the bot restarts or Discord is unavailable before they are delivered
+#[derive(Debug)]
+pub struct Outbox {
+    database: PgPool,
+}
+
+impl Outbox {
+    pub fn new(database: PgPool) -> Outbox {
+        Outbox { database }
+    }
+
+    pub async fn push(
+        connection: &mut PgConnection,
+        recipient: Recipient,
+        query: &Query,
+        old: &ClassRecord,
+        new: &ClassRecord,
+        deliver_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+INSERT INTO notifications (
+  user_id,
+  guild_id,
+  course,
+  semester,
+  career,
+  section,
+  old_timestamp,
+  old,
+  new_timestamp,
+  new,
+  deliver_at
+)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11);
+            "#,
+            recipient.user_id.0 as i64,
+            recipient.guild_id.map(|x| x.0 as i64),
+            query.course,
+            query.semester,
+            query.career,
+            query.section,
+            old.timestamp,
+            Json(&old.model) as _,
+            new.timestamp,
+            Json(&new.model) as _,
+            deliver_at
+        )
+        .execute(connection)
+        .await?;
+
+        Ok(())
+    }
+
+    // claims every due notification, so that no other delivery sends them until they're either
+    // delivered or retried
+    pub async fn pending(&self) -> Result<Vec<Pending>, sqlx::Error> {
+        let now = Utc::now();
+        let mut pending = sqlx::query!(
+            r#"
+UPDATE notifications
+SET claimed_until = $2
+WHERE id IN (
+  SELECT id
+  FROM notifications
+  WHERE
+    delivered_at IS NULL
+    AND
+    dead_at IS NULL
+    AND
+    deliver_at <= $1
+    AND
+    (claimed_until IS NULL OR claimed_until <= $1)
+  FOR UPDATE SKIP LOCKED
+)
+RETURNING
+  id,
+  user_id,
+  guild_id,
+  course,
+  semester,
+  career,
+  section,
+  old_timestamp,
+  old as "old: Json<ClassModel>",
+  new_timestamp,
+  new as "new: Json<ClassModel>";
+            "#,
+            now,
+            now + CLAIM_DURATION
+        )
+        .fetch_all(&self.database)
+        .await?;
+        pending.sort_by_key(|x| x.id);
+
+        Ok(pending
+            .into_iter()
+            .map(|rec| Pending {
+                id: rec.id,
+                notifier: Box::new(Notifier::new(
+                    ClassRecord {
+                        timestamp: rec.new_timestamp,
+                        model: rec.new.0,
+                    },
+                    vec![Recipient {
+                        user_id: UserId(rec.user_id as u64),
+                        guild_id: rec.guild_id.map(|x| GuildId(x as u64)),
+                    }],
+                    Query::from_ids(rec.course, rec.semester, rec.career, rec.section),
+                    Some(ClassRecord {
+                        timestamp: rec.old_timestamp,
+                        model: rec.old.0,
+                    }),
+                )),
+            })
+            .collect())
+    }
+
+    // notifications that haven't been delivered yet, including those held for quiet hours
+    pub async fn backlog(&self) -> Result<i64, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+SELECT COUNT(*) AS "count!"
+FROM notifications
+WHERE
+  delivered_at IS NULL
+  AND
+  dead_at IS NULL;
+            "#
+        )
+        .fetch_one(&self.database)
+        .await?
+        .count)
+    }
+
+    pub async fn delivered(&self, ids: &[i64]) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE notifications SET delivered_at = $1 WHERE id = ANY($2);",
+            Utc::now(),
+            ids
+        )
+        .execute(&self.database)
+        .await?;
+
+        Ok(())
+    }
+
+    // returns the notifications that ran out of attempts and won't be retried
+    pub async fn retry(&self, ids: &[i64]) -> Result<Vec<i64>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+UPDATE notifications
+SET
+  attempts = attempts + 1,
+  deliver_at = $1 + LEAST(INTERVAL '1 minute' * POWER(2, attempts), INTERVAL '1 hour'),
+  claimed_until = NULL,
+  dead_at = CASE WHEN attempts + 1 >= $3 THEN $1 END
+WHERE id = ANY($2)
+RETURNING id, dead_at IS NOT NULL AS "dead!";
+            "#,
+            Utc::now(),
+            ids,
+            MAX_ATTEMPTS
+        )
+        .fetch_all(&self.database)
+        .await?
+        .into_iter()
+        .filter(|x| x.dead)
+        .map(|x| x.id)
+        .collect())
+    }
+
+    // delivered and dead notifications are only kept around for debugging, returns how many were
+    // removed
+    pub async fn prune(
+        &self,
+        delivered_before: DateTime<Utc>,
+        dead_before: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        Ok(sqlx::query!(
+            "DELETE FROM notifications WHERE delivered_at < $1 OR dead_at < $2;",
+            delivered_before,
+            dead_before
+        )
+        .execute(&self.database)
+        .await?
+        .rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::class;
+
+    #[sqlx::test(migrator = "crate::MIGRATOR")]
+    async fn prune_removes_old_delivered_and_dead(database: PgPool) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        let old = now - Duration::days(60);
+        let record = ClassRecord {
+            timestamp: now,
+            model: class("A1", "Lecture", &["Monday"], "09:00", "09:50"),
+        };
+        let query = Query::from_ids("1".into(), "2".into(), "UGRD".into(), "A1".into());
+        let recipient = Recipient {
+            user_id: UserId(1),
+            guild_id: None,
+        };
+        let mut connection = database.acquire().await?;
+        for _ in 0..5 {
+            Outbox::push(&mut connection, recipient, &query, &record, &record, now).await?;
+        }
+
+        let ids = sqlx::query!("SELECT id FROM notifications ORDER BY id;")
+            .fetch_all(&database)
+            .await?
+            .into_iter()
+            .map(|x| x.id)
+            .collect::<Vec<_>>();
+        // delivered and dead long ago, then recently, and the last one is still pending
+        for (id, delivered_at, dead_at) in [
+            (ids[0], Some(old), None),
+            (ids[1], None, Some(old)),
+            (ids[2], Some(now), None),
+            (ids[3], None, Some(now)),
+        ] {
+            sqlx::query!(
+                "UPDATE notifications SET delivered_at = $1, dead_at = $2 WHERE id = $3;",
+                delivered_at,
+                dead_at,
+                id
+            )
+            .execute(&database)
+            .await?;
+        }
+
+        let outbox = Outbox::new(database.clone());
+        let pruned = outbox
+            .prune(now - Duration::days(7), now - Duration::days(30))
+            .await?;
+        assert_eq!(pruned, 2);
+        let left = sqlx::query!("SELECT id FROM notifications ORDER BY id;")
+            .fetch_all(&database)
+            .await?
+            .into_iter()
+            .map(|x| x.id)
+            .collect::<Vec<_>>();
+        assert_eq!(left, ids[2..]);
+        Ok(())
+    }
+}
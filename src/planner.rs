@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+
+use sqlx::types::chrono::{Timelike, Weekday};
+use ubs_lib::model::ClassModel;
+
+use crate::meeting::{self, Meeting};
+
+// classes starting before this hour count as early
+const MORNING_HOUR: u32 = 10;
+// stops enumerating after this many conflict-free schedules, courses with many recitations
+// otherwise blow up combinatorially
+const MAX_CANDIDATES: usize = 10_000;
+// as well as after this many partial schedules, in case most of them conflict and few are ever
+// finished
+const MAX_VISITED: usize = 200_000;
+
+// what a schedule is ranked by, in order of importance
+#[derive(Debug, Clone, Copy)]
+pub struct Preferences {
+    pub open_seats: bool,
+    pub late_start: bool,
+    pub compact: bool,
+}
+
+// one section of every class type for each course, e.g. a lecture and its recitation, owned so
+// that it can outlive the schedules it was built from
+#[derive(Debug)]
+pub struct Plan {
+    // index of the course along with the class
+    pub classes: Vec<(usize, ClassModel)>,
+    // distinct days with classes
+    pub days: usize,
+}
+
+// a choice of sections for a single course
+struct Choice<'a> {
+    classes: Vec<&'a ClassModel>,
+    meetings: Vec<Meeting>,
+}
+
+// conflict-free schedules for the courses, given as the groups of their schedules, best first,
+// blocks for as long as the enumeration takes
+pub fn plans(courses: &[&[Vec<ClassModel>]], preferences: Preferences, limit: usize) -> Vec<Plan> {
+    let mut choices = courses.iter().copied().map(choices).collect::<Vec<_>>();
+    // the best choices are tried first so that they're found before the enumeration is cut off
+    for course in &mut choices {
+        course.sort_by_key(|x| score(&x.classes, &x.meetings, preferences));
+    }
+
+    let mut candidates = Vec::new();
+    enumerate(&choices, &mut Vec::new(), &mut candidates, &mut 0);
+
+    let mut plans = candidates
+        .into_iter()
+        .map(|chosen| {
+            let classes = chosen
+                .iter()
+                .enumerate()
+                .flat_map(|(i, choice)| choice.classes.iter().map(move |x| (i, *x)))
+                .collect::<Vec<_>>();
+            let meetings = chosen
+                .iter()
+                .flat_map(|x| x.meetings.iter().cloned())
+                .collect::<Vec<_>>();
+            let score = score(
+                &classes.iter().map(|(_, x)| *x).collect::<Vec<_>>(),
+                &meetings,
+                preferences,
+            );
+            (score, classes, days(&meetings).len())
+        })
+        .collect::<Vec<_>>();
+    plans.sort_by_key(|(score, _, _)| *score);
+    plans
+        .into_iter()
+        .take(limit)
+        .map(|(_, classes, days)| Plan {
+            classes: classes.into_iter().map(|(i, x)| (i, x.clone())).collect(),
+            days,
+        })
+        .collect()
+}
+
+// every group is a lecture with its recitations and labs, so one section of each type is taken
+// from the same group
+fn choices(groups: &[Vec<ClassModel>]) -> Vec<Choice> {
+    let mut choices = Vec::new();
+    for group in groups {
+        let mut types: Vec<Vec<&ClassModel>> = Vec::new();
+        // sections that can't be watched are left out
+        for class in group.iter().filter(|x| x.section.is_some()) {
+            match types
+                .iter_mut()
+                .find(|x| class_type(x[0]) == class_type(class))
+            {
+                Some(classes) => classes.push(class),
+                None => types.push(vec![class]),
+            }
+        }
+        if types.is_empty() {
+            continue;
+        }
+
+        let mut combinations: Vec<Vec<&ClassModel>> = vec![Vec::new()];
+        for classes in types {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|combination| {
+                    classes.iter().map(move |class| {
+                        let mut combination = combination.clone();
+                        combination.push(*class);
+                        combination
+                    })
+                })
+                .collect();
+        }
+
+        for classes in combinations {
+            if meeting::conflicts(classes.iter().copied()).is_empty() {
+                let meetings = classes.iter().filter_map(|x| Meeting::of(x)).collect();
+                choices.push(Choice { classes, meetings });
+            }
+        }
+    }
+    choices
+}
+
+fn enumerate<'a, 'b>(
+    choices: &'b [Vec<Choice<'a>>],
+    chosen: &mut Vec<&'b Choice<'a>>,
+    candidates: &mut Vec<Vec<&'b Choice<'a>>>,
+    visited: &mut usize,
+) {
+    *visited += 1;
+    if candidates.len() >= MAX_CANDIDATES || *visited > MAX_VISITED {
+        return;
+    }
+    let course = match choices.get(chosen.len()) {
+        Some(course) => course,
+        None => {
+            candidates.push(chosen.clone());
+            return;
+        }
+    };
+
+    for choice in course {
+        if chosen
+            .iter()
+            .any(|x| overlaps(&x.meetings, &choice.meetings))
+        {
+            continue;
+        }
+        chosen.push(choice);
+        enumerate(choices, chosen, candidates, visited);
+        chosen.pop();
+    }
+}
+
+// lower is better, preferences that are turned off don't count
+fn score(
+    classes: &[&ClassModel],
+    meetings: &[Meeting],
+    preferences: Preferences,
+) -> (usize, usize, usize, i64) {
+    let closed = classes.iter().filter(|x| x.is_open == Some(false)).count();
+    let early = meetings
+        .iter()
+        .filter(|x| x.start.hour() < MORNING_HOUR)
+        .count();
+    let days = days(meetings);
+    // minutes spent waiting between classes on the same day
+    let gaps = days
+        .values()
+        .map(|meetings| {
+            let mut meetings = meetings.clone();
+            meetings.sort_by_key(|x| x.start);
+            meetings
+                .windows(2)
+                .map(|x| (x[1].start - x[0].end).num_minutes().max(0))
+                .sum::<i64>()
+        })
+        .sum();
+
+    (
+        if preferences.open_seats { closed } else { 0 },
+        if preferences.late_start { early } else { 0 },
+        if preferences.compact { days.len() } else { 0 },
+        if preferences.compact { gaps } else { 0 },
+    )
+}
+
+fn days(meetings: &[Meeting]) -> HashMap<Weekday, Vec<&Meeting>> {
+    let mut days: HashMap<Weekday, Vec<&Meeting>> = HashMap::new();
+    for meeting in meetings {
+        for day in &meeting.days {
+            days.entry(*day).or_default().push(meeting);
+        }
+    }
+    days
+}
+
+fn overlaps(first: &[Meeting], second: &[Meeting]) -> bool {
+    first.iter().any(|x| second.iter().any(|y| x.overlaps(y)))
+}
+
+fn class_type(class: &ClassModel) -> Option<String> {
+    class.class_type.map(|x| x.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::class;
+
+    const ALL: Preferences = Preferences {
+        open_seats: true,
+        late_start: true,
+        compact: true,
+    };
+
+    fn closed(mut class: ClassModel) -> ClassModel {
+        class.is_open = Some(false);
+        class
+    }
+
+    fn sections(plan: &Plan) -> Vec<(usize, &str)> {
+        plan.classes
+            .iter()
+            .map(|(i, x)| (*i, x.section.as_deref().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn choices_take_every_type_from_the_same_group() {
+        let groups = vec![
+            vec![
+                class("A", "Lecture", &["Monday"], "09:00", "09:50"),
+                class("A1", "Recitation", &["Tuesday"], "09:00", "09:50"),
+                class("A2", "Recitation", &["Wednesday"], "09:00", "09:50"),
+            ],
+            vec![
+                class("B", "Lecture", &["Monday"], "13:00", "13:50"),
+                class("B1", "Recitation", &["Tuesday"], "13:00", "13:50"),
+            ],
+        ];
+        let choices = choices(&groups)
+            .into_iter()
+            .map(|x| {
+                x.classes
+                    .iter()
+                    .map(|x| x.section.clone().unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            choices,
+            vec![vec!["A", "A1"], vec!["A", "A2"], vec!["B", "B1"]]
+        );
+    }
+
+    #[test]
+    fn choices_skip_conflicting_sections() {
+        let groups = vec![vec![
+            class("A", "Lecture", &["Monday"], "09:00", "09:50"),
+            class("A1", "Recitation", &["Monday"], "09:00", "09:50"),
+            class("A2", "Recitation", &["Friday"], "09:00", "09:50"),
+        ]];
+        assert_eq!(choices(&groups).len(), 1);
+    }
+
+    #[test]
+    fn plans_avoid_conflicts_between_courses() {
+        let first = vec![vec![class("A", "Lecture", &["Monday"], "10:00", "10:50")]];
+        let second = vec![
+            vec![class("B", "Lecture", &["Monday"], "10:00", "10:50")],
+            vec![class("C", "Lecture", &["Monday"], "11:00", "11:50")],
+        ];
+        let plans = plans(&[&first, &second], ALL, 10);
+        assert_eq!(plans.len(), 1);
+        assert_eq!(sections(&plans[0]), vec![(0, "A"), (1, "C")]);
+        assert_eq!(plans[0].days, 1);
+    }
+
+    #[test]
+    fn plans_rank_by_preferences() {
+        let course = vec![
+            vec![class("A", "Lecture", &["Monday"], "08:00", "08:50")],
+            vec![closed(class("B", "Lecture", &["Monday"], "11:00", "11:50"))],
+            vec![class("C", "Lecture", &["Monday"], "12:00", "12:50")],
+        ];
+        let ranked = plans(&[&course], ALL, 10)
+            .iter()
+            .map(|x| sections(x)[0].1)
+            .collect::<Vec<_>>();
+        // open seats matter more than starting late
+        assert_eq!(ranked, vec!["C", "A", "B"]);
+        assert_eq!(plans(&[&course], ALL, 1).len(), 1);
+    }
+
+    #[test]
+    fn score_ignores_preferences_that_are_off() {
+        let classes = [
+            closed(class("A", "Lecture", &["Monday"], "08:00", "08:50")),
+            class("B", "Lecture", &["Wednesday"], "13:00", "13:50"),
+        ];
+        let classes = classes.iter().collect::<Vec<_>>();
+        let meetings = classes
+            .iter()
+            .filter_map(|x| Meeting::of(x))
+            .collect::<Vec<_>>();
+        assert_eq!(score(&classes, &meetings, ALL), (1, 1, 2, 0));
+        let none = Preferences {
+            open_seats: false,
+            late_start: false,
+            compact: false,
+        };
+        assert_eq!(score(&classes, &meetings, none), (0, 0, 0, 0));
+    }
+
+    #[test]
+    fn enumeration_stops_when_nothing_fits() {
+        // every section of the first courses fits together, but the last conflicts with all of
+        // them, so no schedule is ever finished
+        let mut courses = (0..7)
+            .map(|i| {
+                (0..6)
+                    .map(|j| {
+                        let start = format!("{:02}:{}0", 8 + i, j);
+                        let end = format!("{:02}:{}5", 8 + i, j);
+                        vec![class(
+                            &format!("S{j}"),
+                            "Lecture",
+                            &["Monday"],
+                            &start,
+                            &end,
+                        )]
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        courses.push(vec![vec![class(
+            "L",
+            "Lecture",
+            &["Monday"],
+            "07:00",
+            "20:00",
+        )]]);
+        let courses = courses.iter().map(|x| x.as_slice()).collect::<Vec<_>>();
+        assert!(plans(&courses, ALL, 10).is_empty());
+    }
+}
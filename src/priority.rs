@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use sqlx::types::chrono::{DateTime, Utc};
+
+use crate::{academic::AcademicCalendar, catalog};
+
+// watchers for a class to be considered popular
+const POPULAR_WATCHERS: i64 = 5;
+// a closed class that changed this recently likely has seats moving, e.g. students swapping sections
+const RECENT_CHANGE: Duration = Duration::from_secs(24 * 60 * 60);
+// a class unchanged for longer is polled less often, the longer the less
+const STALE_AFTER: Duration = Duration::from_secs(2 * 24 * 60 * 60);
+const STALE_STEP: Duration = Duration::from_secs(24 * 60 * 60);
+const MAX_BACKOFF: u32 = 8;
+// while registering, adding or dropping
+const BUSY_FACTOR: f64 = 0.25;
+
+// what's known about a watched class when deciding how often to poll it
+#[derive(Debug, Clone, Copy)]
+pub struct Activity {
+    pub watchers: i64,
+    pub is_open: Option<bool>,
+    // `None` until the class is first cached
+    pub changed_at: Option<DateTime<Utc>>,
+}
+
+// multiplies the interval the class is polled at, so lower is more often
+pub fn factor(
+    calendar: &AcademicCalendar,
+    semester_id: &str,
+    activity: &Activity,
+    now: DateTime<Utc>,
+) -> f64 {
+    let mut factor = 1.0;
+    if activity.watchers >= POPULAR_WATCHERS {
+        factor /= 2.0;
+    }
+
+    // dates are local to UB
+    let today = now.with_timezone(&catalog::TIMEZONE).date_naive();
+    if calendar.is_busy(semester_id, today) {
+        return factor * BUSY_FACTOR;
+    }
+
+    let unchanged = match activity.changed_at {
+        Some(changed_at) => now
+            .signed_duration_since(changed_at)
+            .to_std()
+            .unwrap_or_default(),
+        None => return factor,
+    };
+    if activity.is_open == Some(false) && unchanged <= RECENT_CHANGE {
+        factor /= 2.0;
+    } else if unchanged >= STALE_AFTER {
+        let steps = (unchanged.as_secs() / STALE_STEP.as_secs()) as u32;
+        factor *= steps.min(MAX_BACKOFF) as f64;
+    }
+    factor
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use sqlx::types::chrono::{Duration as ChronoDuration, TimeZone};
+    use ubs_lib::Semester;
+
+    use super::*;
+
+    // not a semester that `ubs-lib` knows, so it's never busy
+    const QUIET_SEMESTER: &str = "0000";
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, 10, 18, 18, 0, 0).unwrap()
+    }
+
+    fn activity(watchers: i64, is_open: Option<bool>, days_unchanged: Option<i64>) -> Activity {
+        Activity {
+            watchers,
+            is_open,
+            changed_at: days_unchanged.map(|x| now() - ChronoDuration::days(x)),
+        }
+    }
+
+    fn factor_of(activity: Activity) -> f64 {
+        factor(
+            &AcademicCalendar::default(),
+            QUIET_SEMESTER,
+            &activity,
+            now(),
+        )
+    }
+
+    #[test]
+    fn popular_classes_are_polled_more() {
+        assert_eq!(factor_of(activity(1, Some(true), Some(1))), 1.0);
+        assert_eq!(
+            factor_of(activity(POPULAR_WATCHERS, Some(true), Some(1))),
+            0.5
+        );
+    }
+
+    #[test]
+    fn recently_changed_closed_classes_are_polled_more() {
+        assert_eq!(factor_of(activity(1, Some(false), Some(0))), 0.5);
+        assert_eq!(
+            factor_of(activity(POPULAR_WATCHERS, Some(false), Some(0))),
+            0.25
+        );
+        // open classes that changed recently aren't any more likely to change again
+        assert_eq!(factor_of(activity(1, Some(true), Some(0))), 1.0);
+    }
+
+    #[test]
+    fn stale_classes_back_off() {
+        assert_eq!(factor_of(activity(1, Some(true), Some(2))), 2.0);
+        assert_eq!(factor_of(activity(1, Some(true), Some(5))), 5.0);
+        assert_eq!(
+            factor_of(activity(1, Some(true), Some(100))),
+            MAX_BACKOFF as f64
+        );
+    }
+
+    #[test]
+    fn uncached_classes_are_not_backed_off() {
+        assert_eq!(factor_of(activity(1, None, None)), 1.0);
+    }
+
+    #[test]
+    fn busy_semesters_are_polled_more() {
+        let semester = Semester::from_str("Fall2023").unwrap();
+        // during the first week of classes, which is approximated as add/drop
+        let now = Utc.with_ymd_and_hms(2023, 8, 30, 16, 0, 0).unwrap();
+        let stale = Activity {
+            watchers: 1,
+            is_open: Some(true),
+            changed_at: Some(now - ChronoDuration::days(100)),
+        };
+        assert_eq!(
+            factor(&AcademicCalendar::default(), semester.id(), &stale, now),
+            BUSY_FACTOR
+        );
+    }
+}
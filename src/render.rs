@@ -0,0 +1,254 @@
+use poise::serenity_prelude::CreateEmbed;
+use ubs_lib::model::ClassModel;
+
+use crate::{
+    cache::{ClassRecord, Query},
+    catalog,
+    translation::{tr, Locale},
+    watcher::WatchError,
+};
+
+const TIME_FORMAT: &str = "%-I:%M%p";
+// tables are tight on space
+const UNKNOWN_CELL: &str = "?";
+const CATALOG_URL: &str = "https://catalog.buffalo.edu/search/";
+// class times are local to UB, most semesters cross a daylight saving change, so the generic
+// abbreviation is used rather than EST or EDT
+const TIMEZONE_LABEL: &str = "ET";
+
+pub fn class_embed<'a>(
+    e: &'a mut CreateEmbed,
+    locale: Locale,
+    query: &Query,
+    record: &ClassRecord,
+) -> &'a mut CreateEmbed {
+    let model = &record.model;
+    let unknown = tr!(locale, "unknown");
+    let course = catalog::course_name(&query.course);
+    e.title(format!(
+        "{} - {}",
+        course.unwrap_or(&query.course),
+        catalog::semester_name(&query.semester)
+            .as_deref()
+            .unwrap_or(&query.semester)
+    ));
+    if let Some(course) = course {
+        e.url(catalog_url(course));
+    }
+
+    e.author(|a| a.name(model.instructor.as_deref().unwrap_or(&unknown)))
+        .timestamp(record.timestamp)
+        // .field("Id", model.class_id.unwrap(), true)
+        .field(
+            tr!(locale, "class-section"),
+            model.section.as_deref().unwrap_or(&unknown),
+            true,
+        )
+        .field(
+            tr!(locale, "class-type"),
+            optional(model.class_type, &unknown),
+            true,
+        )
+        .field(
+            tr!(locale, "class-room"),
+            model.room.as_deref().unwrap_or(&unknown),
+            true,
+        )
+        .field(tr!(locale, "class-open"), open(locale, model.is_open), true)
+        .field(tr!(locale, "class-seats"), seats(model, &unknown), true)
+        .field(
+            tr!(
+                locale,
+                "class-days",
+                count: model.days_of_week.as_ref().map_or(0, |x| x.len())
+            ),
+            match model.days_of_week {
+                Some(ref dow) => dow
+                    .iter()
+                    .map(|x| optional(*x, &unknown))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                None => unknown.clone(),
+            },
+            false,
+        )
+        .field(
+            tr!(locale, "class-time"),
+            format!("{} {TIMEZONE_LABEL}", time_range(model, " — ", &unknown)),
+            true,
+        )
+        // rendered by discord in the user's own timezone
+        .field(
+            tr!(locale, "class-updated"),
+            format!("<t:{}:R>", record.timestamp.timestamp()),
+            true,
+        )
+}
+
+pub fn class_changes(locale: Locale, old: &ClassModel, new: &ClassModel) -> String {
+    let unknown = tr!(locale, "unknown");
+    let mut changes = Vec::new();
+    if old.is_open != new.is_open {
+        changes.push(tr!(
+            locale,
+            "class-changed-open",
+            old: open(locale, old.is_open),
+            new: open(locale, new.is_open)
+        ));
+    }
+    if old.open_seats != new.open_seats || old.total_seats != new.total_seats {
+        changes.push(tr!(
+            locale,
+            "class-changed-seats",
+            old: seats(old, &unknown),
+            new: seats(new, &unknown)
+        ));
+    }
+
+    changes.join("\n")
+}
+
+// code block with a row for every class
+pub fn class_table<'a>(classes: impl IntoIterator<Item = &'a ClassModel>) -> String {
+    let rows = classes
+        .into_iter()
+        .map(|class| {
+            format!(
+                "{:<6}{:<5}{:<10}{:<17}{:<14}{:<16}{}",
+                class.section.as_deref().unwrap_or(UNKNOWN_CELL),
+                truncate(&optional(class.class_type, UNKNOWN_CELL).to_uppercase(), 3),
+                match class.days_of_week {
+                    Some(ref dow) => dow
+                        .iter()
+                        .map(|x| truncate(&optional(*x, UNKNOWN_CELL), 2))
+                        .collect::<String>(),
+                    None => UNKNOWN_CELL.to_owned(),
+                },
+                time_range(class, "-", UNKNOWN_CELL),
+                truncate(class.room.as_deref().unwrap_or(UNKNOWN_CELL), 13),
+                truncate(class.instructor.as_deref().unwrap_or(UNKNOWN_CELL), 15),
+                seats(class, UNKNOWN_CELL),
+            )
+        })
+        .collect::<Vec<String>>();
+
+    // the headers are kept in english, wide characters would break the alignment
+    format!(
+        "```\n{:<6}{:<5}{:<10}{:<17}{:<14}{:<16}{}\n{}\n```",
+        "Sec",
+        "Type",
+        "Days",
+        format!("Time ({TIMEZONE_LABEL})"),
+        "Room",
+        "Instructor",
+        "Seats",
+        rows.join("\n")
+    )
+}
+
+// only the snapshots where seats changed are shown, the cache holds one for every poll
+pub fn history_embed<'a>(
+    e: &'a mut CreateEmbed,
+    locale: Locale,
+    query: &Query,
+    history: &[ClassRecord],
+    limit: usize,
+) -> &'a mut CreateEmbed {
+    let mut entries: Vec<&ClassRecord> = Vec::new();
+    // oldest first, so the earliest snapshot of each change is kept
+    for record in history.iter().rev() {
+        match entries.last() {
+            Some(last)
+                if last.model.is_open == record.model.is_open
+                    && last.model.open_seats == record.model.open_seats
+                    && last.model.total_seats == record.model.total_seats => {}
+            _ => entries.push(record),
+        }
+    }
+
+    let lines = entries
+        .iter()
+        .rev()
+        .take(limit)
+        .map(|x| {
+            tr!(
+                locale,
+                match x.model.is_open {
+                    Some(false) => "history-entry-closed",
+                    _ => "history-entry",
+                },
+                time: format!("<t:{}:f>", x.timestamp.timestamp()),
+                seats: seats(&x.model, UNKNOWN_CELL)
+            )
+        })
+        .collect::<Vec<String>>();
+
+    e.title(tr!(locale, "history-title", class: describe(locale, query)))
+        .description(if lines.is_empty() {
+            tr!(locale, "history-empty")
+        } else {
+            lines.join("\n")
+        })
+}
+
+pub fn describe(locale: Locale, query: &Query) -> String {
+    tr!(
+        locale,
+        "class-description",
+        course: catalog::course_name(&query.course).unwrap_or(&query.course),
+        section: query.section.as_str(),
+        semester: catalog::semester_name(&query.semester).unwrap_or(query.semester.clone())
+    )
+}
+
+// errors that are the user's to fix, anything else is left for the error handler
+pub fn watch_error(locale: Locale, err: &WatchError) -> Option<String> {
+    match err {
+        WatchError::UserLimit(limit) => Some(tr!(locale, "watch-user-limit", limit: *limit)),
+        WatchError::GuildLimit(limit) => Some(tr!(locale, "watch-guild-limit", limit: *limit)),
+        WatchError::Expired => Some(tr!(locale, "watch-semester-over")),
+        WatchError::Sql(_) => None,
+    }
+}
+
+fn seats(model: &ClassModel, unknown: &str) -> String {
+    format!(
+        "{}/{}",
+        optional(model.open_seats, unknown),
+        optional(model.total_seats, unknown)
+    )
+}
+
+fn time_range(model: &ClassModel, separator: &str, unknown: &str) -> String {
+    format!(
+        "{}{}{}",
+        optional(model.start_time.map(|x| x.format(TIME_FORMAT)), unknown),
+        separator,
+        optional(model.end_time.map(|x| x.format(TIME_FORMAT)), unknown)
+    )
+}
+
+fn optional<T: ToString>(value: Option<T>, unknown: &str) -> String {
+    value.map(|x| x.to_string()).unwrap_or(unknown.to_owned())
+}
+
+fn open(locale: Locale, is_open: Option<bool>) -> String {
+    match is_open {
+        Some(is_open) => tr!(locale, "boolean", value: is_open.to_string()),
+        None => tr!(locale, "unknown"),
+    }
+}
+
+// splits "CSE115" into "CSE 115", which is how the catalog refers to courses
+fn catalog_url(course: &str) -> String {
+    let (subject, number) = course.split_at(
+        course
+            .find(|x: char| x.is_ascii_digit())
+            .unwrap_or(course.len()),
+    );
+    format!("{CATALOG_URL}?P={subject}%20{number}")
+}
+
+fn truncate(value: &str, len: usize) -> String {
+    value.chars().take(len).collect()
+}
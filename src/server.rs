@@ -0,0 +1,148 @@
+use std::{
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{extract::State, http::StatusCode, routing::get, Router};
+use poise::serenity_prelude::{ConnectionStage, ShardManager};
+use sqlx::migrate::{Migrate, Migrator};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+use crate::watcher::Watcher;
+
+#[derive(Clone)]
+pub struct ServerState {
+    pub watcher: Arc<Watcher>,
+    pub shard_manager: Arc<Mutex<ShardManager>>,
+    pub migrator: &'static Migrator,
+    // how long the watcher can go without finishing a check of every class
+    pub window: Duration,
+    pub started: Instant,
+}
+
+// whether each part of the bot is working
+#[derive(Debug)]
+struct Report {
+    database: bool,
+    migrations: bool,
+    shards: bool,
+    watcher: bool,
+}
+
+pub async fn serve(
+    addr: SocketAddr,
+    state: ServerState,
+    shutdown: CancellationToken,
+) -> Result<(), crate::Error> {
+    let app = Router::new()
+        .route("/metrics", get(metrics))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .with_state(state);
+
+    info!("serving metrics and health checks on {}", addr);
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .with_graceful_shutdown(shutdown.cancelled())
+        .await?;
+    Ok(())
+}
+
+async fn metrics(State(state): State<ServerState>) -> Result<String, StatusCode> {
+    let cache = state.watcher.cache();
+    cache
+        .metrics()
+        .gather(cache.database())
+        .await
+        .map_err(|err| {
+            error!("failed to gather metrics: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+// the bot is alive as long as it can reach the database and the watcher isn't stuck, a restart
+// wouldn't fix anything else
+async fn healthz(State(state): State<ServerState>) -> (StatusCode, String) {
+    let report = report(&state).await;
+    respond(report.database && report.watcher, &report)
+}
+
+// ready once everything is up, including a first pass over the classes that are due
+async fn readyz(State(state): State<ServerState>) -> (StatusCode, String) {
+    let report = report(&state).await;
+    let ready = report.database
+        && report.migrations
+        && report.shards
+        && report.watcher
+        && state.watcher.last_tick().is_some();
+    respond(ready, &report)
+}
+
+async fn report(state: &ServerState) -> Report {
+    let database = state.watcher.cache().database();
+    let reachable = sqlx::query!(r#"SELECT 1 AS "one!""#)
+        .fetch_one(database)
+        .await
+        .is_ok();
+
+    let migrations = match database.acquire().await {
+        Ok(mut connection) => match connection.list_applied_migrations().await {
+            Ok(applied) => state
+                .migrator
+                .iter()
+                .all(|x| applied.iter().any(|y| y.version == x.version)),
+            Err(err) => {
+                error!("failed to list applied migrations: {:?}", err);
+                false
+            }
+        },
+        Err(_) => false,
+    };
+
+    let shards = {
+        let shard_manager = state.shard_manager.lock().await;
+        let runners = shard_manager.runners.lock().await;
+        !runners.is_empty()
+            && runners
+                .values()
+                .all(|x| x.stage == ConnectionStage::Connected)
+    };
+
+    // the loop has to keep ticking, and once a cycle fails outright, e.g. while UB is down, another
+    // has to succeed within the window. Before then the watcher is given until the end of the
+    // window, and it isn't expected to check anything while paused
+    let watcher = &state.watcher;
+    let ticking = watcher.last_tick().unwrap_or(state.started).elapsed() <= state.window;
+    let fetching = watcher.last_cycle().map_or(true, |x| x.is_successful())
+        || watcher.last_success().unwrap_or(state.started).elapsed() <= state.window;
+    let watcher = watcher.is_paused() || (ticking && fetching);
+
+    Report {
+        database: reachable,
+        migrations,
+        shards,
+        watcher,
+    }
+}
+
+fn respond(ok: bool, report: &Report) -> (StatusCode, String) {
+    let status = if ok {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let body = [
+        ("database", report.database),
+        ("migrations", report.migrations),
+        ("shards", report.shards),
+        ("watcher", report.watcher),
+    ]
+    .iter()
+    .map(|(name, ok)| format!("{name}: {}", if *ok { "ok" } else { "failing" }))
+    .collect::<Vec<_>>()
+    .join("\n");
+    (status, body)
+}
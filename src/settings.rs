@@ -0,0 +1,444 @@
+use chrono_tz::Tz;
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+use sqlx::{
+    types::chrono::{DateTime, NaiveTime, TimeZone, Utc},
+    PgPool,
+};
+
+use crate::catalog;
+
+// UB local time
+pub const DEFAULT_TIMEZONE: Tz = catalog::TIMEZONE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum Delivery {
+    #[name = "Direct message"]
+    Dm,
+    #[name = "Guild channel"]
+    Channel,
+    #[name = "Both"]
+    Both,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Schedule {
+    pub timezone: Tz,
+    pub quiet_hours: Option<(NaiveTime, NaiveTime)>,
+    pub digest_time: Option<NaiveTime>,
+}
+
+#[derive(Debug)]
+pub struct Settings {
+    database: PgPool,
+}
+
+impl Delivery {
+    pub fn id(&self) -> &'static str {
+        match self {
+            Delivery::Dm => "dm",
+            Delivery::Channel => "channel",
+            Delivery::Both => "both",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Delivery> {
+        match id {
+            "dm" => Some(Delivery::Dm),
+            "channel" => Some(Delivery::Channel),
+            "both" => Some(Delivery::Both),
+            _ => None,
+        }
+    }
+}
+
+impl Schedule {
+    // when notifications should be delivered, or `None` if they can be delivered right away
+    pub fn delay(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let local = now.with_timezone(&self.timezone);
+        let time = local.time();
+        let until = match (self.digest_time, self.quiet_hours) {
+            (Some(digest_time), _) => digest_time,
+            (None, Some((start, end))) if is_within(time, start, end) => end,
+            _ => return None,
+        };
+
+        let mut date = local.date_naive();
+        if until <= time {
+            date = date.succ_opt()?;
+        }
+        // if the time doesn't exist locally (a DST transition) it isn't worth holding the notifications
+        self.timezone
+            .from_local_datetime(&date.and_time(until))
+            .earliest()
+            .map(|x| x.with_timezone(&Utc))
+    }
+}
+
+impl Settings {
+    pub fn new(database: PgPool) -> Settings {
+        Settings { database }
+    }
+
+    pub async fn guild_channel(&self, guild_id: GuildId) -> Result<Option<ChannelId>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+SELECT channel_id
+FROM guild_settings
+WHERE
+  $1 in (guild_id);
+            "#,
+            guild_id.0 as i64
+        )
+        .fetch_optional(&self.database)
+        .await?
+        .and_then(|x| x.channel_id)
+        .map(|x| ChannelId(x as u64)))
+    }
+
+    pub async fn set_guild_channel(
+        &self,
+        guild_id: GuildId,
+        channel_id: Option<ChannelId>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+INSERT INTO guild_settings (guild_id, channel_id)
+VALUES ($1, $2)
+ON CONFLICT (guild_id) DO UPDATE
+SET channel_id = EXCLUDED.channel_id;
+            "#,
+            guild_id.0 as i64,
+            channel_id.map(|x| x.0 as i64)
+        )
+        .execute(&self.database)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delivery(&self, user_id: UserId) -> Result<Delivery, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+SELECT delivery
+FROM user_settings
+WHERE
+  $1 in (user_id);
+            "#,
+            user_id.0 as i64
+        )
+        .fetch_optional(&self.database)
+        .await?
+        .and_then(|x| Delivery::from_id(&x.delivery))
+        .unwrap_or(Delivery::Channel))
+    }
+
+    // changing the delivery method gives the user a clean slate, resuming suspended watches
+    pub async fn set_delivery(
+        &self,
+        user_id: UserId,
+        delivery: Delivery,
+    ) -> Result<(), sqlx::Error> {
+        let mut transaction = self.database.begin().await?;
+        sqlx::query!(
+            r#"
+INSERT INTO user_settings (user_id, delivery)
+VALUES ($1, $2)
+ON CONFLICT (user_id) DO UPDATE
+SET delivery = EXCLUDED.delivery, dm_failures = 0;
+            "#,
+            user_id.0 as i64,
+            delivery.id()
+        )
+        .execute(&mut *transaction)
+        .await?;
+        sqlx::query!(
+            "UPDATE watchers SET suspended = FALSE WHERE $1 in (user_id);",
+            user_id.0 as i64
+        )
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn locale(&self, user_id: UserId) -> Result<Option<String>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+SELECT locale
+FROM user_settings
+WHERE
+  $1 in (user_id);
+            "#,
+            user_id.0 as i64
+        )
+        .fetch_optional(&self.database)
+        .await?
+        .and_then(|x| x.locale))
+    }
+
+    pub async fn set_locale(&self, user_id: UserId, locale: &str) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+INSERT INTO user_settings (user_id, locale)
+VALUES ($1, $2)
+ON CONFLICT (user_id) DO UPDATE
+SET locale = EXCLUDED.locale;
+            "#,
+            user_id.0 as i64,
+            locale
+        )
+        .execute(&self.database)
+        .await?;
+
+        Ok(())
+    }
+
+    // returns the number of consecutive failures, including this one
+    pub async fn add_dm_failure(&self, user_id: UserId) -> Result<i32, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+INSERT INTO user_settings (user_id, dm_failures)
+VALUES ($1, 1)
+ON CONFLICT (user_id) DO UPDATE
+SET dm_failures = user_settings.dm_failures + 1
+RETURNING dm_failures;
+            "#,
+            user_id.0 as i64
+        )
+        .fetch_one(&self.database)
+        .await?
+        .dm_failures)
+    }
+
+    pub async fn reset_dm_failures(&self, user_id: UserId) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE user_settings SET dm_failures = 0 WHERE $1 in (user_id) AND dm_failures > 0;",
+            user_id.0 as i64
+        )
+        .execute(&self.database)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn suspend_watches(&self, user_id: UserId) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE watchers SET suspended = TRUE WHERE $1 in (user_id);",
+            user_id.0 as i64
+        )
+        .execute(&self.database)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn schedule(&self, user_id: UserId) -> Result<Schedule, sqlx::Error> {
+        let rec = sqlx::query!(
+            r#"
+SELECT timezone, quiet_start, quiet_end, digest_time
+FROM user_settings
+WHERE
+  $1 in (user_id);
+            "#,
+            user_id.0 as i64
+        )
+        .fetch_optional(&self.database)
+        .await?;
+
+        Ok(match rec {
+            Some(rec) => Schedule {
+                timezone: rec
+                    .timezone
+                    .and_then(|x| x.parse().ok())
+                    .unwrap_or(DEFAULT_TIMEZONE),
+                quiet_hours: rec.quiet_start.zip(rec.quiet_end),
+                digest_time: rec.digest_time,
+            },
+            None => Schedule {
+                timezone: DEFAULT_TIMEZONE,
+                quiet_hours: None,
+                digest_time: None,
+            },
+        })
+    }
+
+    pub async fn set_timezone(&self, user_id: UserId, timezone: Tz) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+INSERT INTO user_settings (user_id, timezone)
+VALUES ($1, $2)
+ON CONFLICT (user_id) DO UPDATE
+SET timezone = EXCLUDED.timezone;
+            "#,
+            user_id.0 as i64,
+            timezone.name()
+        )
+        .execute(&self.database)
+        .await?;
+
+        Ok(())
+    }
+
+    // a semester name such as "Fall2023"
+    pub async fn semester(&self, user_id: UserId) -> Result<Option<String>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+SELECT semester
+FROM user_settings
+WHERE
+  $1 in (user_id);
+            "#,
+            user_id.0 as i64
+        )
+        .fetch_optional(&self.database)
+        .await?
+        .and_then(|x| x.semester))
+    }
+
+    pub async fn set_semester(
+        &self,
+        user_id: UserId,
+        semester: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+INSERT INTO user_settings (user_id, semester)
+VALUES ($1, $2)
+ON CONFLICT (user_id) DO UPDATE
+SET semester = EXCLUDED.semester;
+            "#,
+            user_id.0 as i64,
+            semester
+        )
+        .execute(&self.database)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_quiet_hours(
+        &self,
+        user_id: UserId,
+        quiet_hours: Option<(NaiveTime, NaiveTime)>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+INSERT INTO user_settings (user_id, quiet_start, quiet_end)
+VALUES ($1, $2, $3)
+ON CONFLICT (user_id) DO UPDATE
+SET quiet_start = EXCLUDED.quiet_start, quiet_end = EXCLUDED.quiet_end;
+            "#,
+            user_id.0 as i64,
+            quiet_hours.map(|x| x.0),
+            quiet_hours.map(|x| x.1)
+        )
+        .execute(&self.database)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_digest_time(
+        &self,
+        user_id: UserId,
+        digest_time: Option<NaiveTime>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+INSERT INTO user_settings (user_id, digest_time)
+VALUES ($1, $2)
+ON CONFLICT (user_id) DO UPDATE
+SET digest_time = EXCLUDED.digest_time;
+            "#,
+            user_id.0 as i64,
+            digest_time
+        )
+        .execute(&self.database)
+        .await?;
+
+        Ok(())
+    }
+}
+
+// handles windows that wrap around midnight, e.g. 22:00 to 08:00
+fn is_within(time: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start <= end {
+        start <= time && time < end
+    } else {
+        time >= start || time < end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    // in UB local time
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        DEFAULT_TIMEZONE
+            .with_ymd_and_hms(2023, 10, day, hour, 0, 0)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn schedule(
+        quiet_hours: Option<(NaiveTime, NaiveTime)>,
+        digest_time: Option<NaiveTime>,
+    ) -> Schedule {
+        Schedule {
+            timezone: DEFAULT_TIMEZONE,
+            quiet_hours,
+            digest_time,
+        }
+    }
+
+    #[test]
+    fn no_delay_by_default() {
+        assert_eq!(schedule(None, None).delay(at(18, 3)), None);
+    }
+
+    #[test]
+    fn delayed_until_quiet_hours_end() {
+        let schedule = schedule(Some((time(22), time(7))), None);
+        assert_eq!(schedule.delay(at(18, 23)), Some(at(19, 7)));
+        assert_eq!(schedule.delay(at(18, 3)), Some(at(18, 7)));
+        assert_eq!(schedule.delay(at(18, 7)), None);
+        assert_eq!(schedule.delay(at(18, 12)), None);
+    }
+
+    #[test]
+    fn quiet_hours_within_a_day() {
+        let schedule = schedule(Some((time(9), time(17))), None);
+        assert_eq!(schedule.delay(at(18, 12)), Some(at(18, 17)));
+        assert_eq!(schedule.delay(at(18, 20)), None);
+    }
+
+    #[test]
+    fn delayed_until_the_next_digest() {
+        let schedule = schedule(Some((time(22), time(7))), Some(time(8)));
+        assert_eq!(schedule.delay(at(18, 6)), Some(at(18, 8)));
+        assert_eq!(schedule.delay(at(18, 12)), Some(at(19, 8)));
+        // exactly at the digest waits for the next one
+        assert_eq!(schedule.delay(at(18, 8)), Some(at(19, 8)));
+    }
+
+    #[test]
+    fn delayed_in_the_users_timezone() {
+        let schedule = Schedule {
+            timezone: chrono_tz::Asia::Shanghai,
+            quiet_hours: Some((time(22), time(7))),
+            digest_time: None,
+        };
+        // 23:00 in Shanghai
+        let now = Utc.with_ymd_and_hms(2023, 10, 18, 15, 0, 0).unwrap();
+        assert_eq!(
+            schedule.delay(now),
+            Some(Utc.with_ymd_and_hms(2023, 10, 18, 23, 0, 0).unwrap())
+        );
+    }
+}
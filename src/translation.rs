@@ -0,0 +1,195 @@
+use std::{collections::HashMap, fmt};
+
+use fluent::{FluentArgs, FluentResource};
+use intl_memoizer::concurrent::IntlLangMemoizer;
+use tracing::warn;
+
+use crate::{Context, Data, Error};
+
+// concurrent so that bundles can be shared between tasks
+type FluentBundle = fluent::bundle::FluentBundle<FluentResource, IntlLangMemoizer>;
+
+// what commands are written in, and the fallback for any missing messages
+const DEFAULT_LOCALE: &str = "en-US";
+// embedded so that the bot doesn't depend on its working directory
+// https://discord.com/developers/docs/reference#locales
+const CATALOGS: &[(&str, &str)] = &[
+    ("en-US", include_str!("../translations/en-US.ftl")),
+    ("zh-CN", include_str!("../translations/zh-CN.ftl")),
+];
+
+pub struct Translations {
+    main: FluentBundle,
+    other: HashMap<String, FluentBundle>,
+}
+
+// the messages for a single user, falls back to the default locale
+#[derive(Clone, Copy)]
+pub struct Locale<'a> {
+    translations: &'a Translations,
+    locale: Option<&'a str>,
+}
+
+// e.g. `tr!(locale, "watch-added", class: describe(locale, &query))`
+macro_rules! tr {
+    ($locale:expr, $id:expr $(, $name:ident: $value:expr)* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut args = fluent::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)*
+        $locale.get($id, Some(&args))
+    }};
+}
+pub(crate) use tr;
+
+impl Translations {
+    pub fn load() -> Result<Translations, Error> {
+        let mut main = None;
+        let mut other = HashMap::new();
+        for (locale, source) in CATALOGS {
+            let bundle = bundle(locale, source)?;
+            if *locale == DEFAULT_LOCALE {
+                main = Some(bundle);
+            } else {
+                other.insert(locale.to_string(), bundle);
+            }
+        }
+
+        Ok(Translations {
+            main: main.ok_or("missing catalog for the default locale")?,
+            other,
+        })
+    }
+}
+
+impl fmt::Debug for Translations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Translations")
+            .field("other", &self.other.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> Locale<'a> {
+    pub fn new(translations: &'a Translations, locale: Option<&'a str>) -> Locale<'a> {
+        Locale {
+            translations,
+            locale,
+        }
+    }
+
+    pub fn from_ctx(ctx: Context<'a>) -> Locale<'a> {
+        Locale::new(&ctx.data().translations, ctx.locale())
+    }
+
+    pub fn get(&self, id: &str, args: Option<&FluentArgs<'_>>) -> String {
+        self.locale
+            .and_then(|locale| format(self.translations.other.get(locale)?, id, None, args))
+            .or_else(|| format(&self.translations.main, id, None, args))
+            .unwrap_or_else(|| {
+                warn!("missing message `{}`", id);
+                id.to_owned()
+            })
+    }
+}
+
+// commands are described by the default catalog, and every other catalog can localize them with
+// a message named after the command, e.g.
+//
+// preferences-delivery = 发送方式
+//     .description = ...
+//     .delivery = ...
+//     .delivery-description = ...
+pub fn localize_commands(
+    translations: &Translations,
+    commands: &mut [poise::Command<Data, Error>],
+) {
+    localize_subcommands(translations, commands, None);
+}
+
+fn localize_subcommands(
+    translations: &Translations,
+    commands: &mut [poise::Command<Data, Error>],
+    parent: Option<&str>,
+) {
+    for command in commands {
+        let id = match parent {
+            Some(parent) => format!("{parent}-{}", command.name),
+            None => command.name.clone(),
+        };
+        if command.description.is_none() {
+            command.description = format(&translations.main, &id, Some("description"), None);
+        }
+
+        for (locale, bundle) in &translations.other {
+            if let Some(name) = format(bundle, &id, None, None) {
+                command.name_localizations.insert(locale.clone(), name);
+            }
+            if let Some(description) = format(bundle, &id, Some("description"), None) {
+                command
+                    .description_localizations
+                    .insert(locale.clone(), description);
+            }
+
+            for parameter in &mut command.parameters {
+                if let Some(name) = format(bundle, &id, Some(&parameter.name), None) {
+                    parameter.name_localizations.insert(locale.clone(), name);
+                }
+                if let Some(description) = format(
+                    bundle,
+                    &id,
+                    Some(&format!("{}-description", parameter.name)),
+                    None,
+                ) {
+                    parameter
+                        .description_localizations
+                        .insert(locale.clone(), description);
+                }
+                for choice in &mut parameter.choices {
+                    if let Some(name) = format(bundle, &choice_id(&choice.name), None, None) {
+                        choice.localizations.insert(locale.clone(), name);
+                    }
+                }
+            }
+        }
+
+        localize_subcommands(translations, &mut command.subcommands, Some(&id));
+    }
+}
+
+fn bundle(locale: &str, source: &str) -> Result<FluentBundle, Error> {
+    let resource = FluentResource::try_new(source.to_owned())
+        .map_err(|(_, err)| format!("failed to parse catalog for {locale}: {err:?}"))?;
+    let mut bundle = FluentBundle::new_concurrent(vec![locale
+        .parse()
+        .map_err(|err| format!("invalid locale {locale}: {err}"))?]);
+    // the unicode isolation marks show up in code blocks and break markup such as mentions
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .map_err(|err| format!("failed to add catalog for {locale}: {err:?}"))?;
+    Ok(bundle)
+}
+
+fn format(
+    bundle: &FluentBundle,
+    id: &str,
+    attribute: Option<&str>,
+    args: Option<&FluentArgs<'_>>,
+) -> Option<String> {
+    let message = bundle.get_message(id)?;
+    let pattern = match attribute {
+        Some(attribute) => message.get_attribute(attribute)?.value(),
+        None => message.value()?,
+    };
+    let mut errors = Vec::new();
+    let formatted = bundle.format_pattern(pattern, args, &mut errors);
+    if !errors.is_empty() {
+        warn!("failed to format message `{}`: {:?}", id, errors);
+    }
+    Some(formatted.into_owned())
+}
+
+// choices are named for display, e.g. "Direct message" becomes `choice-direct-message`
+fn choice_id(name: &str) -> String {
+    format!("choice-{}", name.to_lowercase().replace(' ', "-"))
+}
@@ -1,83 +1,993 @@
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
 
-use poise::serenity_prelude::UserId;
+use poise::serenity_prelude::{ChannelId, GuildId, Http, UserId};
+use sqlx::{
+    types::chrono::{self, DateTime, Utc},
+    PgConnection,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, instrument, warn};
+use ubs_lib::model::ClassModel;
 
 use crate::{
-    cache::{Cache, ClassRecord, ClassUpdate, Query},
-    notifier::Notifier,
+    academic::AcademicCalendar,
+    archive::{Archive, Expired},
+    cache::{Cache, ClassRecord, ClassUpdate, FetchClassError, Query},
+    catalog,
+    expiry::Expiry,
+    notifier::{self, Digest, Notifier, Recipient},
+    outbox::{self, Outbox, Pending},
+    priority::{self, Activity},
+    settings::{Delivery, Settings},
+    translation::{Locale, Translations},
 };
 
+// notification and expired watch ids, along with their digest, for each guild a user watched from
+type Parts<'a> = HashMap<Option<GuildId>, (Vec<i64>, Vec<i64>, Digest<'a>)>;
+
+// consecutive direct message failures, without a channel to fall back to, before watches are suspended
+const MAX_DM_FAILURES: i32 = 3;
+// concurrent watches by the same user or in the same guild conflict with each other
+const MAX_WATCH_ATTEMPTS: u32 = 3;
+// https://www.postgresql.org/docs/current/errcodes-appendix.html
+const SERIALIZATION_FAILURE: &str = "40001";
+// the cache table grows with every snapshot, so it's only counted for metrics this often
+const COUNT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
 #[derive(Debug)]
 pub enum Check {
     Old(ClassRecord),
     New(Box<Notifier>), // might as well box it up to reduce footprint
 }
 
+// a single pass of `check_all` over every watched class that's due
+#[derive(Debug, Clone, Copy)]
+pub struct Cycle {
+    pub finished: Instant,
+    pub duration: Duration,
+    pub checked: usize,
+    // classes that couldn't be checked, either fetching from UB or writing to the database failed
+    pub errors: usize,
+}
+
+impl Cycle {
+    // UB being down fails every fetch while the cycle itself still finishes, so it only counts if
+    // something was polled and checked
+    pub fn is_successful(&self) -> bool {
+        self.checked > 0 && self.errors < self.checked
+    }
+}
+
+// how many classes can be watched, a watch adds to the polling load until it's removed
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub user: i64,
+    // across every user in the guild, watches from direct messages don't count
+    pub guild: i64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WatchError {
+    #[error("user has reached the limit of {0} watches")]
+    UserLimit(i64),
+    #[error("guild has reached the limit of {0} watches")]
+    GuildLimit(i64),
+    #[error("add/drop has ended for the semester")]
+    Expired,
+    #[error(transparent)]
+    Sql(#[from] sqlx::Error),
+}
+
+#[derive(Debug)]
+pub struct Stats {
+    pub watches: i64,
+    pub suspended: i64,
+    pub users: i64,
+    pub classes: i64,
+}
+
 #[derive(Debug)]
 pub struct Watcher {
     cache: Cache,
+    settings: Settings,
+    outbox: Outbox,
+    archive: Archive,
+    translations: Arc<Translations>,
+    calendar: Arc<AcademicCalendar>,
+    limits: Limits,
+    // the last time `check_all` polled any class, whether or not anything could be fetched
+    last_cycle: Mutex<Option<Cycle>>,
+    // the last time `check_all` finished with `Cycle::is_successful`
+    last_success: Mutex<Option<Instant>>,
+    // the last time `check_all` finished, even if no class was due, so the loop isn't stuck
+    last_tick: Mutex<Option<Instant>>,
+    // the last time the cache table was counted
+    last_count: Mutex<Option<Instant>>,
+    // polling is skipped while paused, everything else keeps running
+    paused: AtomicBool,
 }
 
 impl Watcher {
-    pub fn new(cache: Cache) -> Watcher {
-        Watcher { cache }
+    pub fn new(
+        cache: Cache,
+        settings: Settings,
+        outbox: Outbox,
+        archive: Archive,
+        translations: Arc<Translations>,
+        calendar: Arc<AcademicCalendar>,
+        limits: Limits,
+    ) -> Watcher {
+        Watcher {
+            cache,
+            settings,
+            outbox,
+            archive,
+            translations,
+            calendar,
+            limits,
+            last_cycle: Mutex::new(None),
+            last_success: Mutex::new(None),
+            last_tick: Mutex::new(None),
+            last_count: Mutex::new(None),
+            paused: AtomicBool::new(false),
+        }
     }
 
     pub fn cache(&self) -> &Cache {
         &self.cache
     }
 
-    pub async fn watch(&self, interval: Duration, max_age: Duration) {
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn outbox(&self) -> &Outbox {
+        &self.outbox
+    }
+
+    pub fn last_cycle(&self) -> Option<Cycle> {
+        *self.last_cycle.lock().unwrap()
+    }
+
+    pub fn last_success(&self) -> Option<Instant> {
+        *self.last_success.lock().unwrap()
+    }
+
+    pub fn last_tick(&self) -> Option<Instant> {
+        *self.last_tick.lock().unwrap()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    // stops once `shutdown` is cancelled, the class being checked is finished first so that its
+    // snapshot and notifications are still written
+    pub async fn watch(
+        &self,
+        interval: Duration,
+        poll_interval: Duration,
+        max_age: Duration,
+        shutdown: CancellationToken,
+    ) {
+        while !shutdown.is_cancelled() {
+            if !self.is_paused() {
+                self.expire_all().await;
+                self.check_all(poll_interval, max_age, &shutdown).await;
+            }
+            tokio::select! {
+                _ = shutdown.cancelled() => {}
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+    }
+
+    // delivers one last time once `shutdown` is cancelled, anything that isn't due yet stays in
+    // the outbox until the next start
+    pub async fn drain(&self, http: &Http, interval: Duration, shutdown: CancellationToken) {
         loop {
-            // TODO: notify checks
-            self.check_all(max_age).await;
-            tokio::time::sleep(interval).await;
+            self.deliver_all(http).await;
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+        self.deliver_all(http).await;
+    }
+
+    // delivered notifications older than `retention`, and dead ones older than `dead_retention`,
+    // are removed every `interval`
+    pub async fn prune(
+        &self,
+        interval: Duration,
+        retention: chrono::Duration,
+        dead_retention: chrono::Duration,
+        shutdown: CancellationToken,
+    ) {
+        while !shutdown.is_cancelled() {
+            let now = Utc::now();
+            match self
+                .outbox
+                .prune(now - retention, now - dead_retention)
+                .await
+            {
+                Ok(0) => {}
+                Ok(removed) => info!("pruned {} delivered or dead notifications", removed),
+                Err(err) => error!("failed to prune notifications: {:?}", err),
+            }
+            tokio::select! {
+                _ = shutdown.cancelled() => {}
+                _ = tokio::time::sleep(interval) => {}
+            }
+        }
+    }
+
+    // every user receives a single digest of all their pending notifications and expired watches
+    pub async fn deliver_all(&self, http: &Http) {
+        let pending = match self.outbox.pending().await {
+            Ok(pending) => pending,
+            Err(err) => {
+                error!("failed to get pending notifications: {:?}", err);
+                return;
+            }
+        };
+        let expired = match self.archive.pending().await {
+            Ok(expired) => expired,
+            Err(err) => {
+                error!("failed to get expired watches: {:?}", err);
+                Vec::new()
+            }
+        };
+
+        // each user's notifications are grouped by the guild they were watched from, so that
+        // each one can still go to its own guild's channel
+        let mut digests: HashMap<UserId, Parts> = HashMap::new();
+        for Pending { id, notifier } in &pending {
+            for recipient in notifier.recipients() {
+                let (ids, _, digest) = digests
+                    .entry(recipient.user_id)
+                    .or_default()
+                    .entry(recipient.guild_id)
+                    .or_default();
+                ids.push(*id);
+                digest.push(notifier);
+            }
+        }
+        for Expired {
+            id,
+            recipient,
+            query,
+        } in &expired
+        {
+            let (_, expired_ids, digest) = digests
+                .entry(recipient.user_id)
+                .or_default()
+                .entry(recipient.guild_id)
+                .or_default();
+            expired_ids.push(*id);
+            digest.push_expired(query);
+        }
+
+        for (user_id, parts) in digests {
+            let delivered = self.deliver(http, user_id, &parts).await;
+            for (guild_id, (ids, expired_ids, _)) in &parts {
+                let delivered = delivered.contains(guild_id);
+                self.cache
+                    .metrics()
+                    .notifications
+                    .with_label_values(&[if delivered { "sent" } else { "failed" }])
+                    .inc_by((ids.len() + expired_ids.len()) as u64);
+                let result = if delivered {
+                    self.outbox
+                        .delivered(ids)
+                        .await
+                        .and(self.archive.delivered(expired_ids).await)
+                } else {
+                    self.retry(user_id, ids, expired_ids).await
+                };
+                if let Err(err) = result {
+                    error!(
+                        "failed to update notifications for user {}: {:?}",
+                        user_id, err
+                    );
+                }
+            }
+        }
+    }
+
+    // failed deliveries are tried again later, until they run out of attempts
+    async fn retry(
+        &self,
+        user_id: UserId,
+        ids: &[i64],
+        expired_ids: &[i64],
+    ) -> Result<(), sqlx::Error> {
+        let mut dead = self.outbox.retry(ids).await?;
+        dead.extend(self.archive.retry(expired_ids).await?);
+        if !dead.is_empty() {
+            error!(
+                "giving up on notifications {:?} for user {} after {} attempts",
+                dead,
+                user_id,
+                outbox::MAX_ATTEMPTS
+            );
+            self.cache
+                .metrics()
+                .notifications
+                .with_label_values(&["dead"])
+                .inc_by(dead.len() as u64);
+        }
+
+        Ok(())
+    }
+
+    // delivers based on the user's preference, everything going through direct messages is sent
+    // as a single digest, if a guild channel isn't configured or the direct message fails, then
+    // the other is used as a fallback
+    //
+    // returns the guilds whose notifications were delivered, the rest should be retried
+    async fn deliver(
+        &self,
+        http: &Http,
+        user_id: UserId,
+        parts: &Parts<'_>,
+    ) -> Vec<Option<GuildId>> {
+        let delivery = match self.settings.delivery(user_id).await {
+            Ok(delivery) => delivery,
+            Err(err) => {
+                error!("failed to get delivery for user {}: {:?}", user_id, err);
+                Delivery::Channel
+            }
+        };
+        // notifications are in the language the user last interacted with
+        let locale = match self.settings.locale(user_id).await {
+            Ok(locale) => locale,
+            Err(err) => {
+                error!("failed to get locale for user {}: {:?}", user_id, err);
+                None
+            }
+        };
+        let locale = Locale::new(&self.translations, locale.as_deref());
+
+        let mut dm = Digest::default();
+        let mut dm_guilds = Vec::new();
+        let mut channels = Vec::new();
+        for (guild_id, (_, _, digest)) in parts {
+            let channel = self.channel(*guild_id).await;
+            if channel.is_none() || delivery != Delivery::Channel {
+                dm.extend(digest);
+                dm_guilds.push(*guild_id);
+            }
+            if let Some(channel) = channel {
+                channels.push((*guild_id, channel, digest));
+            }
         }
+
+        let mut delivered = Vec::new();
+        let mut dm_closed = false;
+        if !dm_guilds.is_empty() {
+            match dm.notify_dm(http, locale, user_id).await {
+                Ok(()) => {
+                    delivered.extend(&dm_guilds);
+                    if let Err(err) = self.settings.reset_dm_failures(user_id).await {
+                        error!(
+                            "failed to reset dm failures for user {}: {:?}",
+                            user_id, err
+                        );
+                    }
+                }
+                Err(err) if notifier::is_dm_closed(&err) => dm_closed = true,
+                Err(err) => error!("failed to notify user {}: {:?}", user_id, err),
+            }
+        }
+
+        for (guild_id, channel, digest) in channels {
+            if delivery == Delivery::Dm && !dm_closed {
+                continue;
+            }
+            match digest.notify(http, locale, channel, Some(user_id)).await {
+                Ok(()) => delivered.push(guild_id),
+                Err(err) => error!("failed to notify channel {}: {:?}", channel, err),
+            }
+        }
+
+        if dm_closed {
+            // retrying won't help for anything without a channel, the user has to open their
+            // direct messages
+            let unreachable = dm_guilds
+                .into_iter()
+                .filter(|x| !delivered.contains(x))
+                .collect::<Vec<_>>();
+            if !unreachable.is_empty() {
+                self.dm_failed(user_id).await;
+                delivered.extend(unreachable);
+            }
+        }
+
+        delivered
     }
 
-    pub async fn check_all(&self, max_age: Duration) -> Vec<Check> {
+    async fn dm_failed(&self, user_id: UserId) {
+        match self.settings.add_dm_failure(user_id).await {
+            Ok(failures) if failures >= MAX_DM_FAILURES => {
+                if let Err(err) = self.settings.suspend_watches(user_id).await {
+                    error!("failed to suspend watches for user {}: {:?}", user_id, err);
+                }
+            }
+            Ok(_) => {}
+            Err(err) => error!("failed to add dm failure for user {}: {:?}", user_id, err),
+        }
+    }
+
+    async fn channel(&self, guild_id: Option<GuildId>) -> Option<ChannelId> {
+        match self.settings.guild_channel(guild_id?).await {
+            Ok(channel) => channel,
+            Err(err) => {
+                error!("failed to get channel for guild {:?}: {:?}", guild_id, err);
+                None
+            }
+        }
+    }
+
+    // removes watches past their expiry and archives the watches of every semester past add/drop,
+    // whose watchers are told on the next delivery
+    pub async fn expire_all(&self) {
+        // watches given an expiry are removed quietly, the user already chose when they end
+        let now = Utc::now();
+        let removed = sqlx::query!("DELETE FROM watchers WHERE expires_at <= $1;", now)
+            .execute(self.cache.database())
+            .await;
+        if let Err(err) = removed {
+            error!("failed to remove expired watches: {:?}", err);
+        }
+
+        let semesters = sqlx::query!("SELECT DISTINCT semester FROM watchers;")
+            .fetch_all(self.cache.database())
+            .await;
+        let semesters = match semesters {
+            Ok(semesters) => semesters,
+            Err(err) => {
+                error!("failed to get watched semesters: {:?}", err);
+                return;
+            }
+        };
+
+        let today = now.with_timezone(&catalog::TIMEZONE).date_naive();
+        let expired = semesters
+            .into_iter()
+            .map(|x| x.semester)
+            .filter(|x| self.calendar.is_over(x, today))
+            .collect::<Vec<_>>();
+        if expired.is_empty() {
+            return;
+        }
+
+        match self.archive_semesters(&expired, now).await {
+            Ok(users) => info!(
+                "archived watches of semesters {:?}, notifying {} users",
+                expired, users
+            ),
+            Err(err) => error!("failed to archive semesters {:?}: {:?}", expired, err),
+        }
+    }
+
+    async fn archive_semesters(
+        &self,
+        semesters: &[String],
+        now: DateTime<Utc>,
+    ) -> Result<usize, sqlx::Error> {
+        let mut transaction = self.cache.database().begin().await?;
+        let users = Archive::archive(&mut *transaction, semesters, now).await?;
+        for user_id in &users {
+            let deliver_at = match self.settings.schedule(*user_id).await {
+                Ok(schedule) => schedule.delay(now),
+                Err(err) => {
+                    error!("failed to get schedule for user {}: {:?}", user_id, err);
+                    None
+                }
+            };
+            if let Some(deliver_at) = deliver_at {
+                Archive::hold(&mut *transaction, *user_id, deliver_at).await?;
+            }
+        }
+        transaction.commit().await?;
+
+        Ok(users.len())
+    }
+
+    // only classes that are due are polled, the most urgent first, each is then scheduled again
+    // after `poll_interval` scaled by its priority, and stops early if `shutdown` is cancelled
+    pub async fn check_all(
+        &self,
+        poll_interval: Duration,
+        max_age: Duration,
+        shutdown: &CancellationToken,
+    ) -> Vec<Check> {
+        let started = Instant::now();
+        let now = Utc::now();
         let queries = sqlx::query!(
             r#"
-SELECT course, semester, career, section
+SELECT
+  course,
+  semester,
+  career,
+  section,
+  COUNT(*) AS "watchers!",
+  changed_at AS "changed_at?",
+  is_open AS "is_open?"
 FROM watchers
-GROUP BY (course, semester, career, section);
-                "#
+LEFT JOIN class_activity USING (course, semester, career, section)
+WHERE NOT suspended AND (next_poll_at IS NULL OR next_poll_at <= $1)
+GROUP BY (course, semester, career, section, changed_at, is_open);
+                "#,
+            now
         )
         .fetch_all(self.cache.database())
-        .await
-        .unwrap(); // TODO: handle
+        .await;
+        let queries = match queries {
+            Ok(queries) => queries,
+            Err(err) => {
+                error!("failed to get watched classes: {:?}", err);
+                return Vec::new();
+            }
+        };
 
+        let mut queries = queries
+            .into_iter()
+            .map(|rec| {
+                let factor = priority::factor(
+                    &self.calendar,
+                    &rec.semester,
+                    &Activity {
+                        watchers: rec.watchers,
+                        is_open: rec.is_open,
+                        changed_at: rec.changed_at,
+                    },
+                    now,
+                );
+                let query = Query::from_ids(rec.course, rec.semester, rec.career, rec.section);
+                (query, factor)
+            })
+            .collect::<Vec<_>>();
+        queries.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+        let checked = queries.len();
+        let mut errors = 0;
         let mut checks = Vec::new();
-        for rec in queries {
-            checks.push(
-                self.check(
-                    Query::from_ids(rec.course, rec.semester, rec.career, rec.section),
-                    max_age,
-                )
-                .await,
-            );
+        for (query, factor) in queries {
+            if shutdown.is_cancelled() {
+                return checks;
+            }
+
+            match self.check(query.clone(), max_age).await {
+                Ok(check) => checks.push(check),
+                Err(err) => {
+                    errors += 1;
+                    error!("failed to check {:?}: {:?}", query, err);
+                }
+            }
+
+            // scheduled even if the fetch failed, so that UB isn't asked again on every tick
+            let next_poll_at = Utc::now()
+                + chrono::Duration::from_std(poll_interval.mul_f64(factor))
+                    .unwrap_or_else(|_| chrono::Duration::zero());
+            if let Err(err) = self.cache.schedule(&query, next_poll_at).await {
+                error!("failed to schedule {:?}: {:?}", query, err);
+            }
         }
 
+        *self.last_tick.lock().unwrap() = Some(Instant::now());
+        // most ticks find nothing due, they're left out so that the stats and health reflect the
+        // last actual poll
+        if checked == 0 {
+            return checks;
+        }
+
+        let cycle = Cycle {
+            finished: Instant::now(),
+            duration: started.elapsed(),
+            checked,
+            errors,
+        };
+        if cycle.is_successful() {
+            *self.last_success.lock().unwrap() = Some(cycle.finished);
+        } else {
+            warn!("every class in the cycle failed to be fetched");
+        }
+        *self.last_cycle.lock().unwrap() = Some(cycle);
+
+        self.count_rows().await;
         checks
     }
 
-    pub async fn check(&self, query: Query, max_age: Duration) -> Check {
-        let update = self.cache.get_or_update(&query, max_age).await.unwrap();
-        match update {
-            ClassUpdate::Old(old) => Check::Old(old),
-            ClassUpdate::New { old, new } => Check::New(Box::new(Notifier::new(
-                new,
-                self.watchers(&query).await,
-                query,
-                old,
-            ))),
+    // updates the `cache_rows` gauge, at most once every `COUNT_INTERVAL`
+    async fn count_rows(&self) {
+        {
+            let mut last_count = self.last_count.lock().unwrap();
+            if last_count.map_or(false, |x| x.elapsed() < COUNT_INTERVAL) {
+                return;
+            }
+            *last_count = Some(Instant::now());
+        }
+        match self.cache.rows().await {
+            Ok(rows) => self.cache.metrics().cache_rows.set(rows),
+            Err(err) => error!("failed to count cached snapshots: {:?}", err),
+        }
+    }
+
+    // the new snapshot and its notifications are written together, so a change is never
+    // recorded without also being delivered
+    #[instrument(skip_all, fields(
+        course = %query.course,
+        semester = %query.semester,
+        career = %query.career,
+        section = %query.section,
+    ))]
+    pub async fn check(&self, query: Query, max_age: Duration) -> Result<Check, FetchClassError> {
+        let (old, new) = match self.cache.get_or_fetch(&query, max_age).await? {
+            ClassUpdate::Old(old) => return Ok(Check::Old(old)),
+            ClassUpdate::New { old, new } => (old, new),
+        };
+
+        let mut transaction = self.cache.database().begin().await?;
+        Cache::insert(&mut *transaction, &query, &new).await?;
+
+        // the first snapshot counts as a change, the class hasn't been seen any other way
+        if old
+            .as_ref()
+            .map_or(true, |old| has_changed(&old.model, &new.model))
+        {
+            Cache::mark_changed(&mut *transaction, &query, &new).await?;
+        }
+
+        let check = match old {
+            Some(old) if has_changed(&old.model, &new.model) => {
+                let now = Utc::now();
+                let recipients = self.watchers(&query).await?;
+                for recipient in &recipients {
+                    let deliver_at = match self.settings.schedule(recipient.user_id).await {
+                        Ok(schedule) => schedule.delay(now).unwrap_or(now),
+                        Err(err) => {
+                            error!(
+                                "failed to get schedule for user {}: {:?}",
+                                recipient.user_id, err
+                            );
+                            now
+                        }
+                    };
+                    Outbox::push(
+                        &mut *transaction,
+                        *recipient,
+                        &query,
+                        &old,
+                        &new,
+                        deliver_at,
+                    )
+                    .await?;
+                }
+                Watcher::remove_once(&mut *transaction, &query, &recipients).await?;
+
+                Check::New(Box::new(Notifier::new(new, recipients, query, Some(old))))
+            }
+            // nothing worth notifying, either it's the first snapshot or nothing changed
+            _ => Check::Old(new),
+        };
+        transaction.commit().await?;
+
+        Ok(check)
+    }
+
+    // watching a class again doesn't count against the limits
+    pub async fn add_watch(
+        &self,
+        recipient: Recipient,
+        query: &Query,
+        expiry: Option<Expiry>,
+    ) -> Result<(), WatchError> {
+        let today = Utc::now().with_timezone(&catalog::TIMEZONE).date_naive();
+        if self.calendar.is_over(&query.semester, today) {
+            return Err(WatchError::Expired);
+        }
+
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match self.insert_watch(recipient, query, expiry).await {
+                Err(WatchError::Sql(err))
+                    if attempts < MAX_WATCH_ATTEMPTS && is_serialization_failure(&err) =>
+                {
+                    continue
+                }
+                result => return result,
+            }
         }
     }
 
-    pub async fn watchers(&self, query: &Query) -> Vec<UserId> {
+    // serializable, so that concurrent watches can't both fit under the limit, whichever commits
+    // last fails and is retried against the new counts
+    async fn insert_watch(
+        &self,
+        recipient: Recipient,
+        query: &Query,
+        expiry: Option<Expiry>,
+    ) -> Result<(), WatchError> {
+        let mut transaction = self.cache.database().begin().await?;
+        sqlx::query!("SET TRANSACTION ISOLATION LEVEL SERIALIZABLE;")
+            .execute(&mut *transaction)
+            .await?;
+
+        let counts = sqlx::query!(
+            r#"
+SELECT
+  COUNT(*) FILTER (WHERE $1 in (user_id)) AS "user!",
+  COUNT(*) FILTER (WHERE $2 in (guild_id)) AS "guild!",
+  COALESCE(BOOL_OR(
+    $1 in (user_id) AND $3 in (course) AND $4 in (semester) AND $5 in (career) AND $6 in (section)
+  ), FALSE) AS "exists!"
+FROM watchers
+WHERE NOT suspended;
+            "#,
+            recipient.user_id.0 as i64,
+            recipient.guild_id.map(|x| x.0 as i64),
+            query.course,
+            query.semester,
+            query.career,
+            query.section
+        )
+        .fetch_one(&mut *transaction)
+        .await?;
+        if !counts.exists {
+            if counts.user >= self.limits.user {
+                return Err(WatchError::UserLimit(self.limits.user));
+            }
+            if recipient.guild_id.is_some() && counts.guild >= self.limits.guild {
+                return Err(WatchError::GuildLimit(self.limits.guild));
+            }
+        }
+
+        // watching again without an expiry, e.g. from a button, leaves the existing one alone
         sqlx::query!(
             r#"
-SELECT user_id
+INSERT INTO watchers (user_id, course, semester, career, section, guild_id, expires_at, once)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+ON CONFLICT (user_id, course, semester, career, section) DO UPDATE
+SET
+  guild_id = EXCLUDED.guild_id,
+  suspended = FALSE,
+  expires_at = CASE WHEN $9 THEN EXCLUDED.expires_at ELSE watchers.expires_at END,
+  once = CASE WHEN $9 THEN EXCLUDED.once ELSE watchers.once END,
+  snoozed_until = CASE WHEN $9 THEN NULL ELSE watchers.snoozed_until END;
+            "#,
+            recipient.user_id.0 as i64,
+            query.course,
+            query.semester,
+            query.career,
+            query.section,
+            recipient.guild_id.map(|x| x.0 as i64),
+            match expiry {
+                Some(Expiry::At(expires_at)) => Some(expires_at),
+                _ => None,
+            },
+            expiry == Some(Expiry::FirstNotification),
+            expiry.is_some()
+        )
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    // returns `false` if the user wasn't watching the class
+    pub async fn remove_watch(&self, user_id: UserId, query: &Query) -> Result<bool, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+DELETE FROM watchers
+WHERE
+  $1 in (user_id)
+  AND
+  $2 in (course)
+  AND
+  $3 in (semester)
+  AND
+  $4 in (career)
+  AND
+  $5 in (section);
+            "#,
+            user_id.0 as i64,
+            query.course,
+            query.semester,
+            query.career,
+            query.section
+        )
+        .execute(self.cache.database())
+        .await?
+        .rows_affected()
+            > 0)
+    }
+
+    // returns `false` if the user wasn't watching the class
+    pub async fn snooze(
+        &self,
+        user_id: UserId,
+        query: &Query,
+        until: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+UPDATE watchers
+SET snoozed_until = $6
+WHERE
+  $1 in (user_id)
+  AND
+  $2 in (course)
+  AND
+  $3 in (semester)
+  AND
+  $4 in (career)
+  AND
+  $5 in (section);
+            "#,
+            user_id.0 as i64,
+            query.course,
+            query.semester,
+            query.career,
+            query.section,
+            until
+        )
+        .execute(self.cache.database())
+        .await?
+        .rows_affected()
+            > 0)
+    }
+
+    async fn remove_once(
+        connection: &mut PgConnection,
+        query: &Query,
+        recipients: &[Recipient],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+DELETE FROM watchers
+WHERE
+  $1 in (course)
+  AND
+  $2 in (semester)
+  AND
+  $3 in (career)
+  AND
+  $4 in (section)
+  AND
+  user_id = ANY($5)
+  AND
+  once;
+            "#,
+            query.course,
+            query.semester,
+            query.career,
+            query.section,
+            &recipients
+                .iter()
+                .map(|x| x.user_id.0 as i64)
+                .collect::<Vec<_>>()
+        )
+        .execute(connection)
+        .await?;
+        Ok(())
+    }
+
+    // every class the user is watching, including suspended watches
+    pub async fn watches(&self, user_id: UserId) -> Result<Vec<Query>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+SELECT course, semester, career, section
+FROM watchers
+WHERE
+  $1 in (user_id);
+            "#,
+            user_id.0 as i64
+        )
+        .fetch_all(self.cache.database())
+        .await?
+        .into_iter()
+        .map(|x| Query::from_ids(x.course, x.semester, x.career, x.section))
+        .collect())
+    }
+
+    pub async fn stats(&self) -> Result<Stats, sqlx::Error> {
+        let rec = sqlx::query!(
+            r#"
+SELECT
+  COUNT(*) AS "watches!",
+  COUNT(*) FILTER (WHERE suspended) AS "suspended!",
+  COUNT(DISTINCT user_id) AS "users!",
+  COUNT(DISTINCT (course, semester, career, section)) AS "classes!"
+FROM watchers;
+            "#
+        )
+        .fetch_one(self.cache.database())
+        .await?;
+
+        Ok(Stats {
+            watches: rec.watches,
+            suspended: rec.suspended,
+            users: rec.users,
+            classes: rec.classes,
+        })
+    }
+
+    // classes with the most watchers, along with how many
+    pub async fn top_watched(&self, limit: i64) -> Result<Vec<(Query, i64)>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+SELECT course, semester, career, section, COUNT(*) AS "watchers!"
+FROM watchers
+GROUP BY (course, semester, career, section)
+ORDER BY COUNT(*) DESC
+LIMIT $1;
+            "#,
+            limit
+        )
+        .fetch_all(self.cache.database())
+        .await?
+        .into_iter()
+        .map(|x| {
+            (
+                Query::from_ids(x.course, x.semester, x.career, x.section),
+                x.watchers,
+            )
+        })
+        .collect())
+    }
+
+    // removes every watch, notification and setting of the user, returns how many watches there were
+    pub async fn purge_user(&self, user_id: UserId) -> Result<u64, sqlx::Error> {
+        let mut transaction = self.cache.database().begin().await?;
+        let watches = sqlx::query!(
+            "DELETE FROM watchers WHERE $1 in (user_id);",
+            user_id.0 as i64
+        )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+        sqlx::query!(
+            "DELETE FROM notifications WHERE $1 in (user_id);",
+            user_id.0 as i64
+        )
+        .execute(&mut *transaction)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM archived_watchers WHERE $1 in (user_id);",
+            user_id.0 as i64
+        )
+        .execute(&mut *transaction)
+        .await?;
+        sqlx::query!(
+            "DELETE FROM user_settings WHERE $1 in (user_id);",
+            user_id.0 as i64
+        )
+        .execute(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+
+        Ok(watches)
+    }
+
+    pub async fn watchers(&self, query: &Query) -> Result<Vec<Recipient>, sqlx::Error> {
+        Ok(sqlx::query!(
+            r#"
+SELECT user_id, guild_id
 FROM watchers
 WHERE
   $1 in (course)
@@ -86,18 +996,69 @@ WHERE
   AND
   $3 in (career)
   AND
-  $4 in (section);
+  $4 in (section)
+  AND
+  NOT suspended
+  AND
+  (expires_at IS NULL OR expires_at > $5)
+  AND
+  (snoozed_until IS NULL OR snoozed_until <= $5);
                 "#,
             query.course,
             query.semester,
             query.career,
             query.section,
+            Utc::now()
         )
         .fetch_all(self.cache.database())
-        .await
-        .unwrap() // TODO: handle
+        .await?
         .iter()
-        .map(|x| UserId(x.user_id as u64))
-        .collect()
+        .map(|x| Recipient {
+            user_id: UserId(x.user_id as u64),
+            guild_id: x.guild_id.map(|x| GuildId(x as u64)),
+        })
+        .collect())
+    }
+}
+
+fn has_changed(old: &ClassModel, new: &ClassModel) -> bool {
+    old.is_open != new.is_open
+        || old.open_seats != new.open_seats
+        || old.total_seats != new.total_seats
+}
+
+fn is_serialization_failure(err: &sqlx::Error) -> bool {
+    err.as_database_error()
+        .and_then(|x| x.code())
+        .map_or(false, |x| x == SERIALIZATION_FAILURE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cycle(checked: usize, errors: usize) -> Cycle {
+        Cycle {
+            finished: Instant::now(),
+            duration: Duration::ZERO,
+            checked,
+            errors,
+        }
+    }
+
+    #[test]
+    fn empty_cycles_are_not_successful() {
+        assert!(!cycle(0, 0).is_successful());
+    }
+
+    #[test]
+    fn cycles_where_every_class_failed_are_not_successful() {
+        assert!(!cycle(3, 3).is_successful());
+    }
+
+    #[test]
+    fn cycles_where_some_class_was_checked_are_successful() {
+        assert!(cycle(3, 0).is_successful());
+        assert!(cycle(3, 2).is_successful());
     }
 }
@@ -0,0 +1,209 @@
+# Messages are looked up by the locale of the interaction, anything missing falls back to this file.
+# Command names and parameters are written in the code, other locales can localize them by their name.
+
+## Shared
+
+unknown = [unknown]
+boolean = { $value ->
+    [true] Yes
+   *[false] No
+}
+page = Page { $page }/{ $pages }
+previous = Previous
+next = Next
+not-author = Only the person who used the command can use these buttons.
+
+## Classes
+
+class-description = { $course }, section { $section }, during { $semester }
+class-section = Section
+class-type = Type
+class-room = Room
+class-open = Open
+class-seats = Seats
+class-days = { $count ->
+    [one] Day of Week
+   *[other] Days of Week
+}
+class-time = Time
+class-updated = Updated
+class-changed-open = Open: { $old } → { $new }
+class-changed-seats = Seats: { $old } → { $new }
+
+history-title = History of { $class }
+history-entry = { $time } — { $seats } seats
+history-entry-closed = { $time } — { $seats } seats (closed)
+history-empty = No history yet.
+
+button-watch = Watch
+button-unwatch = Unwatch
+button-refresh = Refresh
+refresh-cooldown = Please wait { $seconds } seconds before refreshing again.
+button-history = History
+button-rewatch = Watch next term:
+
+## Commands
+
+info-not-found = Could not find { $class }.
+info-missing-mapping = Does this class exist? It may just be missing a mapping.
+    Read here for more information: https://github.com/ok-nick/ubs-bot#why-cant-it-find-a-class-that-i-know-exists
+rawinfo-not-found = Could not find course id { $course }, section { $section }, during semester id { $semester }.
+
+watch-added = Watching { $class }.
+watch-removed = No longer watching { $class }.
+watch-missing = You aren't watching { $class }.
+watch-conflicts = This overlaps with other classes you're watching:
+    { $classes }
+watch-user-limit = You can only watch { $limit } classes at a time, unwatch some to make room.
+watch-guild-limit = This server can only watch { $limit } classes at a time, try watching from a direct message.
+blocked = You've been blocked from using this bot.
+watch-semester-over = Add/drop has ended for this semester, so its seats no longer change.
+watch-expires = This watch ends on { $time }.
+watch-once = This watch ends after its first notification.
+expiry-invalid = Expiries look like `for 3 days`, `until Friday`, `until 2023-10-20` or `after first notification`, and can't be in the past.
+snooze-set = Notifications for { $class } are silenced until { $time }.
+snooze-invalid = Durations look like `3 days`, `12 hours` or `1 week`.
+watch-expired = Add/drop has ended, so these watches expired:
+    { $classes }
+rewatch-unknown = The next semester of { $course } isn't known yet.
+rewatch-missing-section = Section { $section } of { $course } isn't offered during { $semester }, try one of: { $sections }
+
+course-not-found = Could not find any sections of { $course } during { $semester }.
+course-select-section = Select a section for more information
+
+search-not-found = Could not find any courses matching `{ $query }`.
+search-missing-mapping = Is the course missing? It may just be missing a mapping, and only names such as CSE250 are searched, not titles.
+    Read here for more information: https://github.com/ok-nick/ubs-bot#why-cant-it-find-a-class-that-i-know-exists
+search-title = Results for "{ $query }"
+search-info = Info { $course }
+search-watch = Watch { $course }
+search-select-section = Select a section of { $course }.
+search-more-sections = Only the first { $max } sections are listed, use { $command } to see the rest.
+
+sections-invalid = Sections must be listed as course and section pairs, e.g. `CSE115 A1, MTH141 B2`.
+sections-empty = You aren't watching any classes during { $semester }.
+
+calendar-unknown-semester = The dates of { $semester } aren't known.
+calendar-created = { $count ->
+    [one] Import the attached file into your calendar app to add { $count } class.
+   *[other] Import the attached file into your calendar app to add { $count } classes.
+}
+calendar-missing = These classes were left out, either they couldn't be found or they don't meet at a set time:
+    { $classes }
+
+conflicts-none = None of these classes overlap.
+conflicts-found = These classes overlap:
+    { $conflicts }
+conflicts-pair = { $first } with { $second }
+conflicts-missing = These classes couldn't be found:
+    { $classes }
+
+plan-invalid = List between 1 and { $max } courses, e.g. `CSE115, MTH141`.
+plan-none = There's no way to take all of these courses without overlapping classes.
+plan-title = Option { $option } - { $semester }
+plan-days = { $count ->
+    [one] Classes on { $count } day a week.
+   *[other] Classes on { $count } days a week.
+}
+plan-watch = Watch all
+plan-watched = Watching:
+    { $classes }
+
+config-channel-set = Watch notifications will be sent to { $channel }.
+config-channel-unset = Watch notifications will be sent through direct messages.
+
+delivery-dm = Watch notifications will be sent through direct messages, falling back to the guild channel if your direct messages are closed.
+delivery-channel = Watch notifications will be sent to the guild channel, falling back to direct messages if none is configured.
+delivery-both = Watch notifications will be sent through both direct messages and the guild channel.
+timezone-invalid = `{ $timezone }` is not a valid timezone, try something like `America/New_York`.
+timezone-set = Your timezone is now set to `{ $timezone }`.
+time-invalid = Times must be formatted as HH:MM, e.g. `{ $example }`.
+quiet-set = Watch notifications will be held from { $start } to { $end }.
+quiet-disabled = Quiet hours are now disabled.
+quiet-incomplete = Quiet hours need both a start and an end, leave both empty to disable them.
+digest-set = Watch notifications will be summarized daily at { $time }.
+digest-disabled = Watch notifications will be sent as they happen.
+semester-set = Commands will default to { $semester } until its add/drop period ends.
+semester-cleared = Commands will default to the upcoming semester.
+semester-invalid = `{ $semester }` is not a known semester, try something like `Fall2023`.
+semester-unknown = No upcoming semester is known, try giving one such as `Fall2023`.
+
+admin-status-running = Polling is running.
+admin-status-paused = Polling is paused.
+admin-status-cycle = The last cycle finished { $finished }, taking { $duration }s to check { $checked } classes.
+admin-status-errors = { $errors } classes couldn't be checked ({ $rate }%).
+admin-status-no-cycle = No cycle has finished yet.
+admin-status-backlog = { $count ->
+    [one] { $count } notification is waiting to be delivered.
+   *[other] { $count } notifications are waiting to be delivered.
+}
+admin-paused = Polling is paused until `/admin resume` is used.
+admin-resumed = Polling has resumed.
+admin-stats-summary = { $watches } watches ({ $suspended } suspended) by { $users } users across { $classes } classes.
+admin-stats-entry = { $rank }. { $class }: { $count }
+admin-stats-empty = Nothing is being watched.
+admin-purged = Removed { $user } along with { $count } watches.
+admin-blocked = Blocked { $user } and suspended their watches.
+admin-unblocked = Unblocked { $user }.
+admin-not-blocked = { $user } isn't blocked.
+admin-blocklist-entry = { $user }, blocked { $blocked }: { $reason }
+admin-blocklist-empty = Nobody is blocked.
+
+## Command descriptions
+
+info =
+    .description = Get information about a class
+rawinfo =
+    .description = Get information about a class using raw ids
+watch =
+    .description = Notify when a class opens
+unwatch =
+    .description = Stop notifying when a class opens
+snooze =
+    .description = Silence notifications for a watched class for a while
+search =
+    .description = Search for a course by subject and number
+course =
+    .description = List every section of a course
+calendar =
+    .description = Export classes as a calendar file
+conflicts =
+    .description = Check classes for overlapping times
+plan =
+    .description = Build conflict-free schedules from a list of courses
+config =
+    .description = Configure the guild
+config-channel =
+    .description = Set the channel where watch notifications are sent
+preferences =
+    .description = Set your notification preferences
+preferences-delivery =
+    .description = Set how watch notifications are delivered to you
+preferences-timezone =
+    .description = Set the timezone used for your quiet hours and digest
+preferences-quiet =
+    .description = Hold watch notifications during a period of the day
+preferences-digest =
+    .description = Receive a single summary of watch notifications each day
+preferences-semester =
+    .description = Set the semester commands use when none is given
+admin =
+    .description = Operate the bot
+admin-status =
+    .description = Show the health of the poll loop
+admin-pause =
+    .description = Stop polling classes
+admin-resume =
+    .description = Resume polling classes
+admin-force-check =
+    .description = Fetch a class from UB now, notifying its watchers of any change
+admin-stats =
+    .description = Show how many classes are watched
+admin-purge-user =
+    .description = Delete every watch, notification and setting of a user
+admin-block =
+    .description = Stop a user from using the bot and suspend their watches
+admin-unblock =
+    .description = Let a blocked user use the bot again
+admin-blocklist =
+    .description = Show every blocked user
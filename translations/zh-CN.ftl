@@ -0,0 +1,298 @@
+## Shared
+
+unknown = [未知]
+boolean = { $value ->
+    [true] 是
+   *[false] 否
+}
+page = 第 { $page }/{ $pages } 页
+previous = 上一页
+next = 下一页
+not-author = 只有使用该命令的人才能使用这些按钮。
+
+## Classes
+
+class-description = { $course }，{ $section } 班，{ $semester }
+class-section = 班级
+class-type = 类型
+class-room = 教室
+class-open = 开放
+class-seats = 名额
+class-days = 上课日
+class-time = 时间
+class-updated = 更新时间
+class-changed-open = 开放：{ $old } → { $new }
+class-changed-seats = 名额：{ $old } → { $new }
+
+history-title = { $class } 的历史记录
+history-entry = { $time } — { $seats } 个名额
+history-entry-closed = { $time } — { $seats } 个名额（已关闭）
+history-empty = 暂无历史记录。
+
+button-watch = 关注
+button-unwatch = 取消关注
+button-refresh = 刷新
+refresh-cooldown = 请等待 { $seconds } 秒后再刷新。
+button-history = 历史记录
+button-rewatch = 关注下学期：
+
+## Commands
+
+info-not-found = 找不到 { $class }。
+info-missing-mapping = 这门课确实存在吗？可能只是缺少映射。
+    更多信息请参阅：https://github.com/ok-nick/ubs-bot#why-cant-it-find-a-class-that-i-know-exists
+rawinfo-not-found = 找不到课程 ID { $course }、学期 ID { $semester } 的 { $section } 班。
+
+watch-added = 已关注 { $class }。
+watch-removed = 已取消关注 { $class }。
+watch-missing = 你没有关注 { $class }。
+watch-conflicts = 该班级与你关注的其他班级时间冲突：
+    { $classes }
+watch-user-limit = 你最多只能同时关注 { $limit } 个班级，请先取消关注一些班级。
+watch-guild-limit = 此服务器最多只能同时关注 { $limit } 个班级，请尝试在私信中关注。
+blocked = 你已被禁止使用此机器人。
+watch-semester-over = 该学期的加退选已结束，名额不会再变化。
+watch-expires = 此关注将于 { $time } 结束。
+watch-once = 此关注将在第一次通知后结束。
+expiry-invalid = 到期时间应类似于 `for 3 days`、`until Friday`、`until 2023-10-20` 或 `after first notification`，且不能是过去的时间。
+snooze-set = { $class } 的通知已静音至 { $time }。
+snooze-invalid = 时长应类似于 `3 days`、`12 hours` 或 `1 week`。
+watch-expired = 加退选已结束，以下关注已过期：
+    { $classes }
+rewatch-unknown = 尚不知道 { $course } 的下一个学期。
+rewatch-missing-section = { $course } 在 { $semester } 没有开设 { $section } 班级，可以尝试：{ $sections }
+
+course-not-found = 找不到 { $course } 在 { $semester } 的任何班级。
+course-select-section = 选择一个班级以查看详细信息
+
+search-not-found = 找不到与 `{ $query }` 匹配的课程。
+search-missing-mapping = 缺少某门课程？可能只是缺少映射，并且只搜索 CSE250 这样的课程名称，不搜索课程标题。
+    更多信息请参阅：https://github.com/ok-nick/ubs-bot#why-cant-it-find-a-class-that-i-know-exists
+search-title = “{ $query }”的搜索结果
+search-info = { $course } 信息
+search-watch = 关注 { $course }
+search-select-section = 请选择 { $course } 的一个班级。
+search-more-sections = 仅列出前 { $max } 个班级，使用 { $command } 查看其余班级。
+
+sections-invalid = 班级必须以课程和班级代码成对列出，例如 `CSE115 A1, MTH141 B2`。
+sections-empty = 你在 { $semester } 没有关注任何班级。
+
+calendar-unknown-semester = 尚不清楚 { $semester } 的日期。
+calendar-created = 将附件导入你的日历应用，即可添加 { $count } 个班级。
+calendar-missing = 以下班级未被添加，可能是找不到，或者没有固定的上课时间：
+    { $classes }
+
+conflicts-none = 这些班级的时间没有冲突。
+conflicts-found = 以下班级的时间有冲突：
+    { $conflicts }
+conflicts-pair = { $first } 与 { $second }
+conflicts-missing = 找不到以下班级：
+    { $classes }
+
+plan-invalid = 请列出 1 到 { $max } 门课程，例如 `CSE115, MTH141`。
+plan-none = 无法在时间不冲突的情况下选修所有这些课程。
+plan-title = 方案 { $option } - { $semester }
+plan-days = 每周 { $count } 天有课。
+plan-watch = 全部关注
+plan-watched = 已关注：
+    { $classes }
+
+config-channel-set = 关注通知将发送到 { $channel }。
+config-channel-unset = 关注通知将通过私信发送。
+
+delivery-dm = 关注通知将通过私信发送，如果你关闭了私信，则改为发送到服务器频道。
+delivery-channel = 关注通知将发送到服务器频道，如果未设置频道，则改为通过私信发送。
+delivery-both = 关注通知将同时通过私信和服务器频道发送。
+timezone-invalid = `{ $timezone }` 不是有效的时区，请尝试类似 `Asia/Shanghai` 的时区。
+timezone-set = 你的时区已设置为 `{ $timezone }`。
+time-invalid = 时间格式必须为 HH:MM，例如 `{ $example }`。
+quiet-set = 关注通知将在 { $start } 至 { $end } 期间暂缓发送。
+quiet-disabled = 免打扰时段已关闭。
+quiet-incomplete = 免打扰时段需要同时设置开始和结束时间，两者都留空则关闭。
+digest-set = 关注通知将在每天 { $time } 汇总发送。
+digest-disabled = 关注通知将实时发送。
+semester-set = 在 { $semester } 的加退课期结束前，命令将默认使用该学期。
+semester-cleared = 命令将默认使用即将到来的学期。
+semester-invalid = `{ $semester }` 不是已知的学期，请尝试类似 `Fall2023` 的学期。
+semester-unknown = 目前没有已知的下一个学期，请指定一个学期，例如 `Fall2023`。
+
+admin-status-running = 正在轮询。
+admin-status-paused = 轮询已暂停。
+admin-status-cycle = 上一轮于 { $finished } 完成，用时 { $duration } 秒检查了 { $checked } 个班级。
+admin-status-errors = 有 { $errors } 个班级检查失败（{ $rate }%）。
+admin-status-no-cycle = 尚未完成任何一轮检查。
+admin-status-backlog = 有 { $count } 条通知等待发送。
+admin-paused = 轮询已暂停，使用 `/admin resume` 恢复。
+admin-resumed = 轮询已恢复。
+admin-stats-summary = { $users } 位用户关注了 { $classes } 个班级，共 { $watches } 个关注（{ $suspended } 个已暂停）。
+admin-stats-entry = { $rank }. { $class }：{ $count }
+admin-stats-empty = 没有任何关注。
+admin-purged = 已移除 { $user } 及其 { $count } 个关注。
+admin-blocked = 已封禁 { $user } 并暂停其关注。
+admin-unblocked = 已解封 { $user }。
+admin-not-blocked = { $user } 未被封禁。
+admin-blocklist-entry = { $user }，封禁于 { $blocked }：{ $reason }
+admin-blocklist-empty = 没有被封禁的用户。
+
+## Command localizations
+
+info = 信息
+    .description = 获取课程班级的信息
+    .course = 课程
+    .course-description = 课程名称，例如 CSE115
+    .semester = 学期
+    .semester-description = 学期名称，例如 Fall2023，默认为你的偏好或即将到来的学期
+    .section = 班级
+    .section-description = 班级代码，例如 A1
+    .career = 学历层次
+    .career-description = 课程所属的学历层次，默认为本科
+rawinfo = 原始信息
+    .description = 使用原始 ID 获取课程班级的信息
+    .course = 课程
+    .course-description = 课程 ID
+    .semester = 学期
+    .semester-description = 学期 ID
+    .section = 班级
+    .section-description = 班级代码，例如 A1
+    .career = 学历层次
+    .career-description = 学历层次 ID
+watch = 关注
+    .description = 在班级有空位时通知你
+    .course = 课程
+    .course-description = 课程名称，例如 CSE115
+    .semester = 学期
+    .semester-description = 学期名称，例如 Fall2023，默认为你的偏好或即将到来的学期
+    .section = 班级
+    .section-description = 班级代码，例如 A1
+    .career = 学历层次
+    .career-description = 课程所属的学历层次，默认为本科
+    .expires = 到期
+    .expires-description = 何时停止关注，例如 "for 3 days"、"until Friday" 或 "after first notification"
+unwatch = 取消关注
+    .description = 停止关注班级
+    .course = 课程
+    .course-description = 课程名称，例如 CSE115
+    .semester = 学期
+    .semester-description = 学期名称，例如 Fall2023，默认为你的偏好或即将到来的学期
+    .section = 班级
+    .section-description = 班级代码，例如 A1
+    .career = 学历层次
+    .career-description = 课程所属的学历层次，默认为本科
+snooze = 暂停提醒
+    .description = 暂时静音某个关注班级的通知
+    .course = 课程
+    .course-description = 课程名称，例如 CSE115
+    .semester = 学期
+    .semester-description = 学期名称，例如 Fall2023，默认为你的偏好或即将到来的学期
+    .section = 班级
+    .section-description = 班级代码，例如 A1
+    .duration = 时长
+    .duration-description = 静音多久，例如 "3 days" 或 "12 hours"
+    .career = 学历层次
+    .career-description = 课程所属的学历层次，默认为本科
+search = 搜索
+    .description = 按科目和编号搜索课程
+    .query = 关键词
+    .query-description = 课程名称，例如 "CSE 25"，不会搜索 "data structures" 这样的课程标题
+    .semester = 学期
+    .semester-description = 学期名称，例如 Fall2023，默认为你的偏好或即将到来的学期
+course = 课程
+    .description = 列出课程的所有班级
+    .course = 课程
+    .course-description = 课程名称，例如 CSE115
+    .semester = 学期
+    .semester-description = 学期名称，例如 Fall2023，默认为你的偏好或即将到来的学期
+calendar = 日历
+    .description = 将班级导出为日历文件
+    .semester = 学期
+    .semester-description = 学期名称，例如 Fall2023，默认为你的偏好或即将到来的学期
+    .sections = 班级
+    .sections-description = 课程和班级代码，例如 "CSE115 A1, MTH141 B2"，默认为你关注的班级
+conflicts = 冲突
+    .description = 检查班级的时间是否冲突
+    .semester = 学期
+    .semester-description = 学期名称，例如 Fall2023，默认为你的偏好或即将到来的学期
+    .sections = 班级
+    .sections-description = 课程和班级代码，例如 "CSE115 A1, MTH141 B2"，默认为你关注的班级
+plan = 排课
+    .description = 根据课程列表生成时间不冲突的课表
+    .semester = 学期
+    .semester-description = 学期名称，例如 Fall2023，默认为你的偏好或即将到来的学期
+    .courses = 课程
+    .courses-description = 课程名称，例如 "CSE115, MTH141"
+    .open_seats = 有空位
+    .open_seats-description = 优先选择有空位的班级，默认为是
+    .late_start = 晚开始
+    .late_start-description = 优先避免上午 10 点前的课，默认为是
+    .compact = 紧凑
+    .compact-description = 优先选择上课天数少、课间间隔短的课表，默认为是
+config = 配置
+    .description = 配置服务器
+config-channel = 频道
+    .description = 设置发送关注通知的频道
+    .channel = 频道
+    .channel-description = 发送通知的频道，留空则通过私信发送
+preferences = 偏好
+    .description = 设置你的通知偏好
+preferences-delivery = 发送方式
+    .description = 设置关注通知的发送方式
+    .delivery = 发送方式
+    .delivery-description = 关注通知的发送位置
+preferences-timezone = 时区
+    .description = 设置免打扰时段和汇总所使用的时区
+    .timezone = 时区
+    .timezone-description = IANA 时区，例如 America/New_York
+preferences-quiet = 免打扰
+    .description = 在每天的某个时段暂缓发送关注通知
+    .start = 开始
+    .start-description = 免打扰开始时间，格式为 HH:MM，两者都留空则关闭
+    .end = 结束
+    .end-description = 免打扰结束时间，格式为 HH:MM，两者都留空则关闭
+preferences-digest = 汇总
+    .description = 每天接收一次关注通知的汇总
+    .time = 时间
+    .time-description = 发送汇总的时间，格式为 HH:MM，留空则关闭
+preferences-semester = 学期
+    .description = 设置未指定学期时命令所使用的学期
+    .semester = 学期
+    .semester-description = 学期名称，例如 Fall2023，留空则使用即将到来的学期
+
+choice-direct-message = 私信
+choice-guild-channel = 服务器频道
+choice-both = 两者
+admin = 管理
+    .description = 管理机器人
+admin-status = 状态
+    .description = 显示轮询的运行状况
+admin-pause = 暂停
+    .description = 停止轮询班级
+admin-resume = 恢复
+    .description = 恢复轮询班级
+admin-force-check = 强制检查
+    .description = 立即从 UB 获取班级，并在有变化时通知关注者
+    .course = 课程
+    .course-description = 课程名称，例如 CSE115
+    .semester = 学期
+    .semester-description = 学期名称，例如 Fall2023，默认为你的偏好或即将到来的学期
+    .section = 班级
+    .section-description = 班级代码，例如 A1
+    .career = 学历层次
+    .career-description = 课程所属的学历层次，默认为本科
+admin-stats = 统计
+    .description = 显示关注的班级数量
+admin-purge-user = 清除用户
+    .description = 删除用户的所有关注、通知和设置
+    .target = 用户
+    .target-description = 要清除的用户
+admin-block = 封禁
+    .description = 禁止用户使用机器人并暂停其关注
+    .target = 用户
+    .target-description = 要封禁的用户
+    .reason = 原因
+    .reason-description = 封禁原因，仅管理员可见
+admin-unblock = 解封
+    .description = 允许被封禁的用户再次使用机器人
+    .target = 用户
+    .target-description = 要解封的用户
+admin-blocklist = 封禁列表
+    .description = 显示所有被封禁的用户